@@ -0,0 +1,211 @@
+//!
+//! # Gyro Bias Calibration
+//!
+//! Samples are grouped into fixed windows. A window is accepted only
+//! when the board was stationary during it (low gyro variance and an
+//! accel norm close to 1g), and the bias is estimated from the means
+//! of the accepted windows.
+//!
+//! While running, the estimate follows stationary windows slowly.
+//! A window whose mean is far from the estimate is a slow steady
+//! rotation rather than drift, and is never followed.
+//!
+
+use nalgebra::Vector3;
+
+/// Window Length in s
+const WINDOW: f64 = 0.2;
+/// Accepted Windows Needed for Startup Calibration (2s)
+const NEEDED: u32 = 10;
+/// Rejected Windows Allowed before Startup Calibration Fails (20s)
+const REJECT_MAX: u32 = 100;
+
+/// Maximum Gyro Variance per Axis: (0.5dps)² in (rad/s)²
+const GYRO_VAR_MAX: f64 = 7.6e-5;
+/// Maximum Deviation of Accel Norm from 1g, in g
+const ACC_NORM_TOL: f64 = 0.05;
+/// Maximum Bias Magnitude Accepted: 5dps in rad/s
+const BIAS_MAX: f64 = 0.0873;
+/// Maximum Window Deviation from the Bias while Tracking: 0.15dps in rad/s
+const BIAS_STEP: f64 = 0.0026;
+
+/// Bias Tracking Gain per Stationary Window during Normal Running
+const TRACK_GAIN: f64 = 0.02;
+
+///
+/// # Startup Calibration Progress
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Progress {
+    /// Window not yet complete
+    Collecting,
+    /// Window complete, board was stationary
+    Accepted { accepted: u32 },
+    /// Window complete, board was moving
+    Rejected { rejected: u32 },
+    /// Bias estimated
+    Done,
+    /// Board kept moving, or the estimated bias is implausible
+    Failed,
+}
+
+///
+/// # Stationarity Window
+///
+/// Accumulates gyro mean and variance (Welford) over `len` samples.
+///
+#[derive(Default)]
+struct Window {
+    len: u32,
+    n: u32,
+    mean: Vector3<f64>,
+    m2: Vector3<f64>,
+    moving: bool,
+}
+
+impl Window {
+    ///
+    /// # Push Sample
+    ///
+    /// Returns `None` while the window is not full,
+    /// otherwise `Some(mean)` if stationary or `Some(None)` if moving.
+    ///
+    fn push(&mut self, gyro: &Vector3<f64>, acc: &Vector3<f64>) -> Option<Option<Vector3<f64>>> {
+        if (acc.norm() - 1.).abs() > ACC_NORM_TOL {
+            self.moving = true;
+        }
+
+        self.n += 1;
+        let delta = gyro - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta.component_mul(&(gyro - self.mean));
+
+        if self.n < self.len {
+            return None;
+        }
+
+        let var = self.m2 / (self.n - 1) as f64;
+        let still = !self.moving && var.iter().all(|&v| v < GYRO_VAR_MAX);
+        let mean = self.mean;
+        *self = Self {
+            len: self.len,
+            ..Self::default()
+        };
+
+        Some(still.then_some(mean))
+    }
+}
+
+///
+/// # Gyro Bias Estimator
+///
+#[derive(Default)]
+pub struct GyroBias {
+    window: Window,
+    sum: Vector3<f64>,
+    accepted: u32,
+    rejected: u32,
+    bias: Vector3<f64>,
+}
+
+impl GyroBias {
+    ///
+    /// # New Estimator
+    ///
+    /// `odr` is the gyro output data rate in Hz.
+    ///
+    pub fn new(odr: f64) -> Self {
+        let window = Window {
+            len: ((odr * WINDOW) as u32).max(2),
+            ..Window::default()
+        };
+
+        Self {
+            window,
+            ..Self::default()
+        }
+    }
+
+    ///
+    /// # Get Bias
+    ///
+    /// Returns the current bias estimation in rad/s.
+    ///
+    pub fn bias(&self) -> Vector3<f64> {
+        self.bias
+    }
+
+    ///
+    /// # Correct Sample
+    ///
+    /// Subtract the estimated bias from a raw gyro sample.
+    ///
+    pub fn correct(&self, gyro: &Vector3<f64>) -> Vector3<f64> {
+        gyro - self.bias
+    }
+}
+
+impl GyroBias {
+    ///
+    /// # Startup Calibration
+    ///
+    /// Feed raw gyro (rad/s) and accel (g) samples until
+    /// [`Progress::Done`] or [`Progress::Failed`] is returned.
+    ///
+    pub fn startup(&mut self, gyro: &Vector3<f64>, acc: &Vector3<f64>) -> Progress {
+        let Some(verdict) = self.window.push(gyro, acc) else {
+            return Progress::Collecting;
+        };
+
+        let Some(mean) = verdict else {
+            self.rejected += 1;
+            return match self.rejected < REJECT_MAX {
+                true => Progress::Rejected {
+                    rejected: self.rejected,
+                },
+                false => Progress::Failed,
+            };
+        };
+
+        self.accepted += 1;
+        self.sum += mean;
+
+        if self.accepted < NEEDED {
+            return Progress::Accepted {
+                accepted: self.accepted,
+            };
+        }
+
+        let bias = self.sum / self.accepted as f64;
+        if bias.norm() > BIAS_MAX {
+            return Progress::Failed;
+        }
+
+        self.bias = bias;
+        Progress::Done
+    }
+
+    ///
+    /// # Track Bias
+    ///
+    /// Slowly follow the bias while the board is stationary, ignoring
+    /// windows more than `BIAS_STEP` off the current estimate.
+    ///
+    /// Returns `true` when a stationary window was absorbed.
+    ///
+    pub fn track(&mut self, gyro: &Vector3<f64>, acc: &Vector3<f64>) -> bool {
+        let Some(Some(mean)) = self.window.push(gyro, acc) else {
+            return false;
+        };
+
+        // A Steady Slow Rotation Passes as Stationary, Never Follow it
+        let step = mean - self.bias;
+        if mean.norm() >= BIAS_MAX || step.norm() >= BIAS_STEP {
+            return false;
+        }
+
+        self.bias += step * TRACK_GAIN;
+        true
+    }
+}
@@ -0,0 +1,179 @@
+//!
+//! # Error-State Kalman Filter
+//!
+//! The nominal state is the attitude quaternion and the gyro bias,
+//! the error state is the local attitude error `δθ` and the bias error
+//! `δb`, with the covariance `P` over `[δθ, δb]`.
+//!
+//! - Predict: integrate the bias corrected gyro, propagate `P`.
+//! - Correct: compare the measured gravity direction with the
+//!   predicted one, inject the error into the nominal state.
+//!
+
+use crate::Estimator;
+use nalgebra::{Matrix3, Matrix6, SMatrix, UnitQuaternion, Vector3, Vector6};
+
+type Matrix3x6 = SMatrix<f64, 3, 6>;
+
+/// Initial Attitude Uncertainty: (0.1rad)²
+const P_ATT: f64 = 1e-2;
+/// Initial Bias Uncertainty: (0.01rad/s)²
+const P_BIAS: f64 = 1e-4;
+
+///
+/// # ESKF Gains
+///
+/// Noise densities of the process and measurement models.
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EskfGains {
+    /// Gyro Noise in rad/s/√Hz
+    pub gyro_noise: f64,
+    /// Gyro Bias Random Walk in rad/s²/√Hz
+    pub bias_noise: f64,
+    /// Acc Direction Noise, Unitless
+    pub acc_noise: f64,
+}
+
+impl EskfGains {
+    pub const DEFAULT: Self = Self {
+        gyro_noise: 1e-3,
+        bias_noise: 1e-5,
+        acc_noise: 5e-2,
+    };
+}
+
+pub struct Eskf {
+    quat: UnitQuaternion<f64>,
+    bias: Vector3<f64>,
+    p: Matrix6<f64>,
+    gains: EskfGains,
+}
+
+impl Eskf {
+    pub fn new(quat: UnitQuaternion<f64>, gains: EskfGains) -> Self {
+        Self {
+            quat,
+            bias: Vector3::zeros(),
+            p: initial_p(),
+            gains,
+        }
+    }
+
+    ///
+    /// # Get Gains
+    ///
+    pub fn gains(&self) -> EskfGains {
+        self.gains
+    }
+
+    ///
+    /// # Set Gains
+    ///
+    /// Takes effect from the next update, the covariance is kept.
+    ///
+    pub fn set_gains(&mut self, gains: EskfGains) {
+        self.gains = gains;
+    }
+
+    ///
+    /// # Get Covariance
+    ///
+    /// Returns the error state covariance over `[δθ, δb]`.
+    ///
+    pub fn covariance(&self) -> &Matrix6<f64> {
+        &self.p
+    }
+}
+
+impl Eskf {
+    fn predict(&mut self, gyro: &Vector3<f64>, dt: f64) {
+        let w = gyro - self.bias;
+        let rot = UnitQuaternion::from_scaled_axis(w * dt);
+        self.quat *= rot;
+
+        // F = [[Rᵀ(ω·dt), -I·dt], [0, I]]
+        let mut f = Matrix6::identity();
+        f.fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&rot.to_rotation_matrix().matrix().transpose());
+        f.fixed_view_mut::<3, 3>(0, 3)
+            .copy_from(&(Matrix3::identity() * -dt));
+
+        let g = &self.gains;
+        let mut q = Matrix6::zeros();
+        q.fixed_view_mut::<3, 3>(0, 0)
+            .fill_diagonal(g.gyro_noise * g.gyro_noise * dt);
+        q.fixed_view_mut::<3, 3>(3, 3)
+            .fill_diagonal(g.bias_noise * g.bias_noise * dt);
+
+        self.p = f * self.p * f.transpose() + q;
+    }
+
+    fn correct(&mut self, acc: &Vector3<f64>) {
+        let Some(z) = acc.try_normalize(1e-6) else {
+            return;
+        };
+
+        // Predicted Gravity Direction in the Body Frame
+        let h = self.quat.inverse_transform_vector(&Vector3::z());
+
+        // H = [[h]×, 0]
+        let mut hm = Matrix3x6::zeros();
+        hm.fixed_view_mut::<3, 3>(0, 0).copy_from(&h.cross_matrix());
+
+        let var = self.gains.acc_noise * self.gains.acc_noise;
+        let s = hm * self.p * hm.transpose() + Matrix3::identity() * var;
+        let Some(s_inv) = s.try_inverse() else {
+            return;
+        };
+
+        let k = self.p * hm.transpose() * s_inv;
+        let dx: Vector6<f64> = k * (z - h);
+
+        // Inject the Error State
+        let dtheta = dx.fixed_rows::<3>(0).into_owned();
+        self.quat *= UnitQuaternion::from_scaled_axis(dtheta);
+        self.bias += dx.fixed_rows::<3>(3);
+
+        // Joseph Form, keeps P Symmetric and Positive
+        let ikh = Matrix6::identity() - k * hm;
+        let r = Matrix3::identity() * var;
+        self.p = ikh * self.p * ikh.transpose() + k * r * k.transpose();
+    }
+}
+
+impl Estimator for Eskf {
+    fn update(
+        &mut self,
+        gyro: &Vector3<f64>,
+        acc: Option<&Vector3<f64>>,
+        dt: f64,
+    ) -> UnitQuaternion<f64> {
+        self.predict(gyro, dt);
+        if let Some(acc) = acc {
+            self.correct(acc);
+        }
+
+        self.quat
+    }
+
+    fn quat(&self) -> UnitQuaternion<f64> {
+        self.quat
+    }
+
+    fn reset(&mut self, quat: UnitQuaternion<f64>) {
+        self.quat = quat;
+        self.bias = Vector3::zeros();
+        self.p = initial_p();
+    }
+
+    fn gyro_bias(&self) -> Option<Vector3<f64>> {
+        Some(self.bias)
+    }
+}
+
+fn initial_p() -> Matrix6<f64> {
+    let d = Vector6::new(P_ATT, P_ATT, P_ATT, P_BIAS, P_BIAS, P_BIAS);
+    Matrix6::from_diagonal(&d)
+}
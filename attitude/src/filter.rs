@@ -0,0 +1,161 @@
+//!
+//! # Runtime Filter Selection
+//!
+
+use crate::{Eskf, EskfGains, Estimator};
+use crate::{Madgwick, MadgwickGains, Mahony, MahonyGains};
+use nalgebra::{UnitQuaternion, Vector3};
+
+///
+/// # Filter Kind
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Kind {
+    Mahony,
+    Madgwick,
+    Eskf,
+}
+
+///
+/// # Filter Gains
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Gains {
+    Mahony(MahonyGains),
+    Madgwick(MadgwickGains),
+    Eskf(EskfGains),
+}
+
+impl Kind {
+    ///
+    /// # Default Gains
+    ///
+    pub const fn gains(self) -> Gains {
+        match self {
+            Self::Mahony => Gains::Mahony(MahonyGains::DEFAULT),
+            Self::Madgwick => Gains::Madgwick(MadgwickGains::DEFAULT),
+            Self::Eskf => Gains::Eskf(EskfGains::DEFAULT),
+        }
+    }
+}
+
+impl Gains {
+    ///
+    /// # Filter Kind of the Gains
+    ///
+    pub const fn kind(&self) -> Kind {
+        match self {
+            Self::Mahony(_) => Kind::Mahony,
+            Self::Madgwick(_) => Kind::Madgwick,
+            Self::Eskf(_) => Kind::Eskf,
+        }
+    }
+}
+
+///
+/// # Runtime Selected Filter
+///
+/// Not boxed in `no_std`, always takes the size of the [`Eskf`].
+///
+#[allow(clippy::large_enum_variant)]
+pub enum Filter {
+    Mahony(Mahony),
+    Madgwick(Madgwick),
+    Eskf(Eskf),
+}
+
+impl Filter {
+    pub fn new(quat: UnitQuaternion<f64>, gains: Gains) -> Self {
+        match gains {
+            Gains::Mahony(g) => Self::Mahony(Mahony::new(quat, g)),
+            Gains::Madgwick(g) => Self::Madgwick(Madgwick::new(quat, g)),
+            Gains::Eskf(g) => Self::Eskf(Eskf::new(quat, g)),
+        }
+    }
+
+    ///
+    /// # Get Filter Kind
+    ///
+    pub fn kind(&self) -> Kind {
+        self.gains().kind()
+    }
+
+    ///
+    /// # Get Gains
+    ///
+    pub fn gains(&self) -> Gains {
+        match self {
+            Self::Mahony(f) => Gains::Mahony(f.gains()),
+            Self::Madgwick(f) => Gains::Madgwick(f.gains()),
+            Self::Eskf(f) => Gains::Eskf(f.gains()),
+        }
+    }
+
+    ///
+    /// # Tune
+    ///
+    /// Applies the gains, switching the filter if the kind differs.
+    /// A switched filter restarts from the current attitude.
+    ///
+    pub fn tune(&mut self, gains: Gains) {
+        match (self, gains) {
+            (Self::Mahony(f), Gains::Mahony(g)) => f.set_gains(g),
+            (Self::Madgwick(f), Gains::Madgwick(g)) => f.set_gains(g),
+            (Self::Eskf(f), Gains::Eskf(g)) => f.set_gains(g),
+            (this, gains) => *this = Self::new(this.quat(), gains),
+        }
+    }
+
+    ///
+    /// # Switch Filter
+    ///
+    /// Switches to `kind` with its default gains, keeping
+    /// the current attitude. Does nothing if already running.
+    ///
+    pub fn switch(&mut self, kind: Kind) {
+        if self.kind() != kind {
+            self.tune(kind.gains());
+        }
+    }
+
+    fn inner(&mut self) -> &mut dyn Estimator {
+        match self {
+            Self::Mahony(f) => f,
+            Self::Madgwick(f) => f,
+            Self::Eskf(f) => f,
+        }
+    }
+
+    fn inner_ref(&self) -> &dyn Estimator {
+        match self {
+            Self::Mahony(f) => f,
+            Self::Madgwick(f) => f,
+            Self::Eskf(f) => f,
+        }
+    }
+}
+
+impl Estimator for Filter {
+    fn update(
+        &mut self,
+        gyro: &Vector3<f64>,
+        acc: Option<&Vector3<f64>>,
+        dt: f64,
+    ) -> UnitQuaternion<f64> {
+        self.inner().update(gyro, acc, dt)
+    }
+
+    fn quat(&self) -> UnitQuaternion<f64> {
+        self.inner_ref().quat()
+    }
+
+    fn reset(&mut self, quat: UnitQuaternion<f64>) {
+        self.inner().reset(quat)
+    }
+
+    fn gyro_bias(&self) -> Option<Vector3<f64>> {
+        self.inner_ref().gyro_bias()
+    }
+}
@@ -0,0 +1,176 @@
+//!
+//! # Angles and Frames
+//!
+//! Outputs derived from the attitude quaternion, which rotates the
+//! body frame into the world frame:
+//!
+//! - [`euler`]: Tait-Bryan angles in any [`RotationOrder`]
+//! - [`Heading`]: continuous yaw with a turn count and zeroing
+//! - [`world_rate`]: angular velocity in the world frame
+//! - [`linear_acc`]: accel with gravity removed
+//!
+
+use core::f64::consts::{PI, TAU};
+use nalgebra::{ComplexField, RealField, UnitQuaternion, Vector3};
+
+///
+/// # Rotation Order
+///
+/// Intrinsic sequence from the world to the body frame, e.g. [`Zyx`]
+/// is yaw about Z, then pitch about the new Y, then roll about the
+/// new X.
+///
+/// [`Zyx`]: RotationOrder::Zyx
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RotationOrder {
+    /// Yaw, Pitch, Roll: Aerospace
+    Zyx,
+    /// Yaw, Roll, Pitch: Gimbals with the Pitch Axis Innermost
+    Zxy,
+    Yxz,
+    Yzx,
+    Xyz,
+    Xzy,
+}
+
+impl RotationOrder {
+    /// Axis Indices in Rotation Order
+    const fn axes(self) -> [usize; 3] {
+        match self {
+            Self::Zyx => [2, 1, 0],
+            Self::Zxy => [2, 0, 1],
+            Self::Yxz => [1, 0, 2],
+            Self::Yzx => [1, 2, 0],
+            Self::Xyz => [0, 1, 2],
+            Self::Xzy => [0, 2, 1],
+        }
+    }
+}
+
+///
+/// # Euler Angles
+///
+/// Decomposes `quat` in `order`. The angles are indexed by axis:
+/// roll about X, pitch about Y, yaw about Z, all in rad.
+///
+/// The middle angle is within ±90°, the others within ±180°.
+///
+pub fn euler(quat: &UnitQuaternion<f64>, order: RotationOrder) -> Vector3<f64> {
+    let r = quat.to_rotation_matrix().into_inner();
+    let [a, b, c] = order.axes();
+
+    // +1 for Cyclic Orders (XYZ, YZX, ZXY), -1 Otherwise
+    let s = match (b + 3 - a) % 3 {
+        1 => 1.,
+        _ => -1.,
+    };
+
+    let mut angles = Vector3::zeros();
+    angles[a] = RealField::atan2(-s * r[(b, c)], r[(c, c)]);
+    angles[b] = ComplexField::asin((s * r[(a, c)]).clamp(-1., 1.));
+    angles[c] = RealField::atan2(-s * r[(a, b)], r[(a, a)]);
+    angles
+}
+
+///
+/// # Continuous Heading
+///
+/// Yaw about the world Z axis, counting whole turns instead of
+/// wrapping at ±180°, relative to a zero set by [`Heading::zero`].
+///
+#[derive(Clone, Copy, Debug)]
+pub struct Heading {
+    offset: UnitQuaternion<f64>,
+    last: f64,
+    turns: i32,
+}
+
+impl Default for Heading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heading {
+    pub fn new() -> Self {
+        Self {
+            offset: UnitQuaternion::identity(),
+            last: 0.,
+            turns: 0,
+        }
+    }
+
+    ///
+    /// # Update
+    ///
+    /// Call with every attitude, at least twice per turn.
+    /// Returns the attitude with the yaw zero applied.
+    ///
+    pub fn update(&mut self, quat: &UnitQuaternion<f64>) -> UnitQuaternion<f64> {
+        let quat = self.offset * quat;
+        let yaw = yaw(&quat);
+
+        match yaw - self.last {
+            d if d > PI => self.turns -= 1,
+            d if d < -PI => self.turns += 1,
+            _ => {}
+        }
+
+        self.last = yaw;
+        quat
+    }
+
+    ///
+    /// # Zero Yaw
+    ///
+    /// The current heading of `quat` becomes zero, turns restart.
+    ///
+    pub fn zero(&mut self, quat: &UnitQuaternion<f64>) {
+        self.offset = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -yaw(quat));
+        self.last = 0.;
+        self.turns = 0;
+    }
+
+    ///
+    /// # Continuous Yaw in rad
+    ///
+    pub fn yaw(&self) -> f64 {
+        self.last + self.turns as f64 * TAU
+    }
+
+    ///
+    /// # Whole Turns
+    ///
+    /// Signed, rounded towards zero.
+    ///
+    pub fn turns(&self) -> i32 {
+        (self.yaw() / TAU) as i32
+    }
+}
+
+/// Heading of the Body X Axis, ±180°
+fn yaw(quat: &UnitQuaternion<f64>) -> f64 {
+    euler(quat, RotationOrder::Zyx).z
+}
+
+///
+/// # World Frame Angular Velocity
+///
+/// `gyro` in the body frame, in rad/s.
+///
+pub fn world_rate(quat: &UnitQuaternion<f64>, gyro: &Vector3<f64>) -> Vector3<f64> {
+    quat * gyro
+}
+
+///
+/// # Linear Acceleration
+///
+/// Removes gravity from `acc` in g, the specific force measured in the
+/// body frame. Returns `(body, world)`, both in g.
+///
+pub fn linear_acc(quat: &UnitQuaternion<f64>, acc: &Vector3<f64>) -> (Vector3<f64>, Vector3<f64>) {
+    let world = quat * acc - Vector3::z();
+    (quat.inverse_transform_vector(&world), world)
+}
@@ -0,0 +1,85 @@
+//!
+//! # Attitude Estimators
+//!
+//! Interchangeable attitude filters behind the [`Estimator`] trait:
+//!
+//! - [`Mahony`]: Complementary Filter with PI Feedback
+//! - [`Madgwick`]: Gradient Descent Filter
+//! - [`Eskf`]: Quaternion Error-State Kalman Filter, with Gyro Bias
+//!
+//! [`Filter`] selects one of them at runtime, [`frames`] derives the
+//! angles, rates and linear acceleration from its attitude. The gyro
+//! bias is calibrated and tracked by [`bias`].
+//!
+//! The attitude quaternion rotates the body frame into the world
+//! frame, the world Z axis points up, against gravity.
+//!
+//! The accel may come slower than the gyro. [`Mahony`] and [`Madgwick`]
+//! integrate the gyro over every `dt`, but each accel correction over
+//! the time since the previous one, so their gains hold at any accel
+//! rate.
+//!
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod bias;
+pub mod frames;
+
+mod eskf;
+mod filter;
+mod madgwick;
+mod mahony;
+
+#[cfg(test)]
+mod tests;
+
+pub use eskf::{Eskf, EskfGains};
+pub use filter::{Filter, Gains, Kind};
+pub use frames::{Heading, RotationOrder};
+pub use madgwick::{Madgwick, MadgwickGains};
+pub use mahony::{Mahony, MahonyGains};
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// Longest Accel Period a Correction Spans in s, after a Gap
+const ACC_DT_MAX: f64 = 0.05;
+
+///
+/// # Attitude Estimator
+///
+pub trait Estimator {
+    ///
+    /// # Update
+    ///
+    /// Propagate with `gyro` in rad/s over `dt` in s, and correct with
+    /// the `acc` direction when given. An `acc` with a vanishing norm
+    /// is ignored.
+    ///
+    fn update(
+        &mut self,
+        gyro: &Vector3<f64>,
+        acc: Option<&Vector3<f64>>,
+        dt: f64,
+    ) -> UnitQuaternion<f64>;
+
+    ///
+    /// # Get Attitude
+    ///
+    fn quat(&self) -> UnitQuaternion<f64>;
+
+    ///
+    /// # Reset Attitude
+    ///
+    /// Restart from `quat`, clearing the internal filter state.
+    ///
+    fn reset(&mut self, quat: UnitQuaternion<f64>);
+
+    ///
+    /// # Get Gyro Bias
+    ///
+    /// Returns the estimated gyro bias in rad/s, if estimated.
+    ///
+    fn gyro_bias(&self) -> Option<Vector3<f64>> {
+        None
+    }
+}
@@ -0,0 +1,84 @@
+//!
+//! # Madgwick Filter
+//!
+
+use crate::{ACC_DT_MAX, Estimator};
+use ahrs::Ahrs;
+use nalgebra::{UnitQuaternion, Vector3};
+
+///
+/// # Madgwick Gains
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MadgwickGains {
+    /// Gradient Descent Step in rad/s
+    pub beta: f64,
+}
+
+impl MadgwickGains {
+    pub const DEFAULT: Self = Self { beta: 0.1 };
+}
+
+pub struct Madgwick {
+    inner: ahrs::Madgwick<f64>,
+    /// Time since the Last Accel Correction in s
+    since: f64,
+}
+
+impl Madgwick {
+    pub fn new(quat: UnitQuaternion<f64>, gains: MadgwickGains) -> Self {
+        let inner = ahrs::Madgwick::new_with_quat(0., gains.beta, quat);
+        Self { inner, since: 0. }
+    }
+
+    ///
+    /// # Get Gains
+    ///
+    pub fn gains(&self) -> MadgwickGains {
+        MadgwickGains {
+            beta: self.inner.beta(),
+        }
+    }
+
+    ///
+    /// # Set Gains
+    ///
+    /// Takes effect from the next update.
+    ///
+    pub fn set_gains(&mut self, gains: MadgwickGains) {
+        *self.inner.beta_mut() = gains.beta;
+    }
+}
+
+impl Estimator for Madgwick {
+    fn update(
+        &mut self,
+        gyro: &Vector3<f64>,
+        acc: Option<&Vector3<f64>>,
+        dt: f64,
+    ) -> UnitQuaternion<f64> {
+        *self.inner.sample_period_mut() = dt;
+        self.inner.update_gyro(gyro);
+        self.since += dt;
+
+        // Correction Alone over the Accel Period, not the Gyro dt
+        if let Some(acc) = acc {
+            *self.inner.sample_period_mut() = self.since.min(ACC_DT_MAX);
+            if self.inner.update_imu(&Vector3::zeros(), acc).is_ok() {
+                self.since = 0.;
+            }
+        }
+
+        self.inner.quat()
+    }
+
+    fn quat(&self) -> UnitQuaternion<f64> {
+        self.inner.quat()
+    }
+
+    fn reset(&mut self, quat: UnitQuaternion<f64>) {
+        *self.inner.quat_mut() = quat;
+        self.since = 0.;
+    }
+}
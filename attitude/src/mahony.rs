@@ -0,0 +1,89 @@
+//!
+//! # Mahony Filter
+//!
+
+use crate::{ACC_DT_MAX, Estimator};
+use ahrs::Ahrs;
+use nalgebra::{UnitQuaternion, Vector3};
+
+///
+/// # Mahony Gains
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MahonyGains {
+    /// Proportional Gain in 1/s
+    pub kp: f64,
+    /// Integral Gain in 1/s²
+    pub ki: f64,
+}
+
+impl MahonyGains {
+    pub const DEFAULT: Self = Self { kp: 3.5, ki: 0. };
+}
+
+pub struct Mahony {
+    inner: ahrs::Mahony<f64>,
+    /// Time since the Last Accel Correction in s
+    since: f64,
+}
+
+impl Mahony {
+    pub fn new(quat: UnitQuaternion<f64>, gains: MahonyGains) -> Self {
+        let inner = ahrs::Mahony::new_with_quat(0., gains.kp, gains.ki, quat);
+        Self { inner, since: 0. }
+    }
+
+    ///
+    /// # Get Gains
+    ///
+    pub fn gains(&self) -> MahonyGains {
+        MahonyGains {
+            kp: self.inner.kp(),
+            ki: self.inner.ki(),
+        }
+    }
+
+    ///
+    /// # Set Gains
+    ///
+    /// Takes effect from the next update.
+    ///
+    pub fn set_gains(&mut self, gains: MahonyGains) {
+        *self.inner.kp_mut() = gains.kp;
+        *self.inner.ki_mut() = gains.ki;
+    }
+}
+
+impl Estimator for Mahony {
+    fn update(
+        &mut self,
+        gyro: &Vector3<f64>,
+        acc: Option<&Vector3<f64>>,
+        dt: f64,
+    ) -> UnitQuaternion<f64> {
+        *self.inner.sample_period_mut() = dt;
+        self.inner.update_gyro(gyro);
+        self.since += dt;
+
+        // Correction Alone over the Accel Period, not the Gyro dt
+        if let Some(acc) = acc {
+            *self.inner.sample_period_mut() = self.since.min(ACC_DT_MAX);
+            if self.inner.update_imu(&Vector3::zeros(), acc).is_ok() {
+                self.since = 0.;
+            }
+        }
+
+        self.inner.quat()
+    }
+
+    fn quat(&self) -> UnitQuaternion<f64> {
+        self.inner.quat()
+    }
+
+    fn reset(&mut self, quat: UnitQuaternion<f64>) {
+        *self.inner.quat_mut() = quat;
+        *self.inner.e_int_mut() = Vector3::zeros();
+        self.since = 0.;
+    }
+}
@@ -0,0 +1,325 @@
+//!
+//! # Host Tests
+//!
+//! Run with `cargo test -p attitude --target host-tuple`.
+//!
+//! Sample sequences are recorded from a known trajectory with noise
+//! and bias, then replayed through every estimator.
+//!
+
+use crate::bias::{GyroBias, Progress};
+use crate::frames::{euler, linear_acc, world_rate};
+use crate::{Eskf, EskfGains, Estimator, Filter, Gains, Heading, Kind, RotationOrder};
+use crate::{Madgwick, MadgwickGains, Mahony, MahonyGains};
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// Sample Period in s
+const DT: f64 = 0.001;
+/// Gyro Noise Amplitude in rad/s
+const GYRO_NOISE: f64 = 0.005;
+/// Acc Noise Amplitude in g
+const ACC_NOISE: f64 = 0.01;
+
+/// Recorded Sample: Gyro in rad/s, Acc in g
+type Sample = (Vector3<f64>, Vector3<f64>);
+
+///
+/// # Noise Source
+///
+/// Deterministic Xorshift, uniform in `-1 ~ 1`.
+///
+struct Noise(u64);
+
+impl Noise {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 52) as f64 - 1.
+    }
+
+    fn vec(&mut self, amp: f64) -> Vector3<f64> {
+        Vector3::new(self.next(), self.next(), self.next()) * amp
+    }
+}
+
+///
+/// # Record a Trajectory
+///
+/// Starts at `quat` and rotates at the body rates returned by `rate(t)`
+/// for `secs`, returns the samples and the final true attitude.
+///
+fn record(
+    quat: UnitQuaternion<f64>,
+    secs: f64,
+    bias: Vector3<f64>,
+    rate: impl Fn(f64) -> Vector3<f64>,
+) -> (Vec<Sample>, UnitQuaternion<f64>) {
+    let mut noise = Noise(0x2545_F491_4F6C_DD1D);
+    let mut truth = quat;
+    let mut samples = Vec::new();
+
+    for i in 0..(secs / DT) as usize {
+        let w = rate(i as f64 * DT);
+        truth *= UnitQuaternion::from_scaled_axis(w * DT);
+
+        let gyro = w + bias + noise.vec(GYRO_NOISE);
+        let acc = truth.inverse_transform_vector(&Vector3::z()) + noise.vec(ACC_NOISE);
+        samples.push((gyro, acc));
+    }
+
+    (samples, truth)
+}
+
+fn replay(est: &mut impl Estimator, samples: &[Sample]) -> UnitQuaternion<f64> {
+    for (gyro, acc) in samples {
+        est.update(gyro, Some(acc), DT);
+    }
+    est.quat()
+}
+
+fn filters() -> [Filter; 3] {
+    let q = UnitQuaternion::identity();
+    [
+        Filter::Mahony(Mahony::new(q, MahonyGains::DEFAULT)),
+        Filter::Madgwick(Madgwick::new(q, MadgwickGains::DEFAULT)),
+        Filter::Eskf(Eskf::new(q, EskfGains::DEFAULT)),
+    ]
+}
+
+/// Tilt Error in rad, Ignoring the Unobservable Yaw
+fn tilt_error(est: &UnitQuaternion<f64>, truth: &UnitQuaternion<f64>) -> f64 {
+    let a = est.inverse_transform_vector(&Vector3::z());
+    let b = truth.inverse_transform_vector(&Vector3::z());
+    a.angle(&b)
+}
+
+#[test]
+fn converge_static_tilt() {
+    let tilt = UnitQuaternion::from_euler_angles(0.3, -0.2, 0.);
+    let (samples, truth) = record(tilt, 20., Vector3::zeros(), |_| Vector3::zeros());
+
+    for mut f in filters() {
+        let quat = replay(&mut f, &samples);
+        let err = tilt_error(&quat, &truth);
+        assert!(err < 0.01, "{:?}: Tilt Error {} rad", f.kind(), err);
+    }
+}
+
+#[test]
+fn gains_hold_at_slow_acc() {
+    let tilt = UnitQuaternion::from_euler_angles(0.3, -0.2, 0.);
+    let (samples, truth) = record(tilt, 1., Vector3::zeros(), |_| Vector3::zeros());
+
+    // Acc on every Sample, or on every 5th as the BMI088 at Default Rates
+    for kind in [Kind::Mahony, Kind::Madgwick] {
+        let [fast, slow] = [1, 5].map(|every: usize| {
+            let mut f = Filter::new(UnitQuaternion::identity(), kind.gains());
+            for (i, (gyro, acc)) in samples.iter().enumerate() {
+                f.update(gyro, (i % every == 0).then_some(acc), DT);
+            }
+            tilt_error(&f.quat(), &truth)
+        });
+
+        assert!(fast < 0.3, "{:?}: Tilt Error {} rad", kind, fast);
+        assert!(
+            (slow - fast).abs() < 0.1 * fast,
+            "{:?}: {} vs {} rad",
+            kind,
+            slow,
+            fast
+        );
+    }
+}
+
+#[test]
+fn track_rotation() {
+    // 90° about X in 1.5s, then 90° about Y in 1.5s, then Still
+    let rate = |t: f64| match t {
+        t if t < 1.5 => Vector3::new(core::f64::consts::FRAC_PI_3, 0., 0.),
+        t if t < 3.0 => Vector3::new(0., core::f64::consts::FRAC_PI_3, 0.),
+        _ => Vector3::zeros(),
+    };
+    let start = UnitQuaternion::from_euler_angles(0.1, 0.1, 0.);
+    let (samples, truth) = record(start, 10., Vector3::zeros(), rate);
+
+    for mut f in filters() {
+        f.reset(start);
+        let quat = replay(&mut f, &samples);
+        let err = quat.angle_to(&truth);
+        assert!(err < 0.02, "{:?}: Attitude Error {} rad", f.kind(), err);
+    }
+}
+
+#[test]
+fn eskf_estimates_bias() {
+    let bias = Vector3::new(0.01, -0.02, 0.005);
+    let tilt = UnitQuaternion::from_euler_angles(0.2, 0.1, 0.);
+    let (samples, truth) = record(tilt, 60., bias, |_| Vector3::zeros());
+
+    let mut eskf = Eskf::new(UnitQuaternion::identity(), EskfGains::DEFAULT);
+    let quat = replay(&mut eskf, &samples);
+    assert!(tilt_error(&quat, &truth) < 0.005);
+
+    // Only the Bias Perpendicular to Gravity is Observable
+    let g = truth.inverse_transform_vector(&Vector3::z());
+    let est = eskf.gyro_bias().unwrap();
+    let err = (est - bias) - g * g.dot(&(est - bias));
+    assert!(err.norm() < 1e-3, "Bias Error {}", err.norm());
+
+    // The Other Filters do not Estimate the Bias
+    assert!(filters()[..2].iter().all(|f: _| f.gyro_bias().is_none()));
+}
+
+#[test]
+fn missing_acc_integrates_gyro() {
+    let w = Vector3::new(0., 0., 1.);
+    let truth = UnitQuaternion::from_scaled_axis(w * 0.5);
+
+    for mut f in filters() {
+        for i in 0..500 {
+            // Every other Sample without Acc, or with a Zero Acc
+            let acc = (i % 2 == 0).then_some(Vector3::zeros());
+            f.update(&w, acc.as_ref(), DT);
+        }
+
+        let err = f.quat().angle_to(&truth);
+        assert!(err < 1e-6, "{:?}: Attitude Error {} rad", f.kind(), err);
+    }
+}
+
+#[test]
+fn switch_and_tune() {
+    let tilt = UnitQuaternion::from_euler_angles(0.3, 0.2, 0.1);
+    let mut f = Filter::new(tilt, Kind::Mahony.gains());
+    assert_eq!(f.kind(), Kind::Mahony);
+
+    let gains = Gains::Mahony(MahonyGains { kp: 1., ki: 0.1 });
+    f.tune(gains);
+    assert_eq!(f.gains(), gains);
+
+    // Switching Keeps the Attitude
+    f.switch(Kind::Eskf);
+    assert_eq!(f.kind(), Kind::Eskf);
+    assert!(f.quat().angle_to(&tilt) < 1e-12);
+
+    let gains = Gains::Madgwick(MadgwickGains { beta: 0.05 });
+    f.tune(gains);
+    assert_eq!((f.kind(), f.gains()), (Kind::Madgwick, gains));
+    assert!(f.quat().angle_to(&tilt) < 1e-12);
+}
+
+#[test]
+fn euler_orders_round_trip() {
+    use RotationOrder::*;
+
+    // Within ±90° on every Axis, any may be the Middle Angle
+    let angles = Vector3::new(0.3, -0.7, 1.2);
+    for order in [Zyx, Zxy, Yxz, Yzx, Xyz, Xzy] {
+        // Compose the Intrinsic Sequence
+        let axes = match order {
+            Zyx => [2, 1, 0],
+            Zxy => [2, 0, 1],
+            Yxz => [1, 0, 2],
+            Yzx => [1, 2, 0],
+            Xyz => [0, 1, 2],
+            Xzy => [0, 2, 1],
+        };
+        let quat = axes.iter().fold(UnitQuaternion::identity(), |q: _, &i: _| {
+            let axis = Vector3::ith_axis(i);
+            q * UnitQuaternion::from_axis_angle(&axis, angles[i])
+        });
+
+        let err = (euler(&quat, order) - angles).norm();
+        assert!(err < 1e-9, "{:?}: Angle Error {}", order, err);
+    }
+
+    // ZYX Matches the Roll, Pitch, Yaw of nalgebra
+    let quat = UnitQuaternion::from_euler_angles(0.3, -0.7, 2.5);
+    let err = euler(&quat, Zyx) - Vector3::new(0.3, -0.7, 2.5);
+    assert!(err.norm() < 1e-9);
+}
+
+#[test]
+fn heading_counts_turns() {
+    let mut heading = Heading::new();
+    let yaw = |a: f64| UnitQuaternion::from_euler_angles(0.1, -0.1, a);
+
+    // Two and a Half Turns Counter-Clockwise, then Back
+    let step = 0.1;
+    let mut a = 0.;
+    while a < 5. * core::f64::consts::PI {
+        heading.update(&yaw(a));
+        a += step;
+    }
+    assert!((heading.yaw() - (a - step)).abs() < 1e-9);
+    assert_eq!(heading.turns(), 2);
+
+    while a > -1. {
+        a -= step;
+        heading.update(&yaw(a));
+    }
+    assert!((heading.yaw() - a).abs() < 1e-9);
+    assert_eq!(heading.turns(), 0);
+
+    // Zeroing Rotates the Output, Tilt is Kept
+    let q = yaw(2.);
+    heading.zero(&q);
+    let out = heading.update(&q);
+    assert!(heading.yaw().abs() < 1e-9);
+    assert!(tilt_error(&out, &q) < 1e-9);
+}
+
+#[test]
+fn rates_and_linear_acc() {
+    let quat = UnitQuaternion::from_euler_angles(0., 0., core::f64::consts::FRAC_PI_2);
+
+    // Body X Points to World Y
+    let w = world_rate(&quat, &Vector3::new(1., 0., 0.));
+    assert!((w - Vector3::new(0., 1., 0.)).norm() < 1e-12);
+
+    // At Rest the Accel Reads +1g Up, Nothing Left after Gravity
+    let tilt = UnitQuaternion::from_euler_angles(0.4, -0.3, 1.);
+    let rest = tilt.inverse_transform_vector(&Vector3::z());
+    let (body, world) = linear_acc(&tilt, &rest);
+    assert!(body.norm() < 1e-12 && world.norm() < 1e-12);
+
+    // Accelerating along World X
+    let push = Vector3::new(0.5, 0., 0.);
+    let acc = tilt.inverse_transform_vector(&(Vector3::z() + push));
+    let (body, world) = linear_acc(&tilt, &acc);
+    assert!((world - push).norm() < 1e-12);
+    assert!((tilt * body - push).norm() < 1e-12);
+}
+
+#[test]
+fn bias_ignores_slow_rotation() {
+    let dps = core::f64::consts::PI / 180.;
+    let bias = Vector3::new(0.3, -0.2, 0.1) * dps;
+    let acc = Vector3::new(0., 0., 1.);
+    let mut noise = Noise(7);
+    let mut est = GyroBias::new(1. / DT);
+
+    // Startup at Rest
+    let mut progress = Progress::Collecting;
+    while !matches!(progress, Progress::Done | Progress::Failed) {
+        progress = est.startup(&(bias + noise.vec(GYRO_NOISE)), &acc);
+    }
+    assert_eq!(progress, Progress::Done);
+    assert!((est.bias() - bias).norm() < 1e-3);
+
+    // Steady 2dps Turn: Stationary by Variance, but Never Followed
+    let turn = Vector3::new(0., 0., 2. * dps);
+    for _ in 0..10_000 {
+        assert!(!est.track(&(bias + turn + noise.vec(GYRO_NOISE)), &acc));
+    }
+    assert!((est.bias() - bias).norm() < 1e-3);
+
+    // Small Drift is Followed
+    let drift = bias + Vector3::new(0.05, 0., 0.) * dps;
+    let tracked = (0..10_000)
+        .filter(|_: &_| est.track(&(drift + noise.vec(GYRO_NOISE)), &acc))
+        .count();
+    assert_eq!(tracked, 50);
+    assert!((est.bias() - drift).norm() < (est.bias() - bias).norm());
+}
@@ -1,21 +1,51 @@
 //!
 //! # Health Task
 //!
+//! Ticks the heartbeats and grades the system health by the
+//! criticality of the devices lost after being online:
+//!
+//! - Critical: [`SysMode::Error`].
+//! - Degraded: [`SysMode::Degraded`], the system keeps running.
+//! - Informational: reported only.
+//!
+//! Once the devices come back the mode recovers to `Disarmed`.
+//! Nothing is applied before boot has finished: booting, self-testing
+//! and calibrating end in their own mode first.
+//!
+//! Also prints the device statistics table on request, the
+//! profiling report and the probe table when due, and feeds the
+//! watchdog while every watched task is in time.
+//!
 
 use crate::{system::*, time::Instant};
-use utils::init_ticker;
+use utils::devices::{self, Health};
+use utils::{init_ticker, probe, profile};
 
 #[embassy_executor::task]
-pub async fn task() -> ! {
+pub async fn task(p: WatchdogSrc) -> ! {
+    let mut wdg = Task::watchdog(p.iwdg_p);
     let mut t = init_ticker!(Device::interval(), ms);
 
     let mut last = Instant::now();
 
     loop {
         for device in WATCH_LIST {
-            if !device.tick() {
-                SysMode::Error.set();
-            }
+            device.tick();
+        }
+
+        Task::feed(&mut wdg);
+
+        let health = Device::health();
+        grade(health);
+        supervise(health);
+
+        if devices::stats_requested() {
+            Device::log_stats();
+        }
+
+        if profile::report_due() {
+            profile::log_report();
+            probe::log_table();
         }
 
         if last.elapsed().as_secs() >= 1 {
@@ -30,3 +60,51 @@ pub async fn task() -> ! {
         t.next().await
     }
 }
+
+///
+/// # Grade System Health
+///
+/// Publishes the health and logs every change.
+///
+fn grade(health: Health) {
+    if health == Health::get() {
+        return;
+    }
+
+    match health {
+        Health::Nominal => defmt::info!("System Health Nominal"),
+        Health::Degraded => defmt::warn!("System Health Degraded"),
+        Health::Critical => defmt::error!("System Health Critical!!!"),
+    }
+
+    health.set();
+}
+
+///
+/// # Apply Health to the System Mode
+///
+/// Retried every tick, so a mode that cannot change right now
+/// follows as soon as it can, e.g. once calibrated.
+///
+fn supervise(health: Health) {
+    let recoverable = SysMode::cause().is_some_and(Reason::is_device);
+
+    let (mode, reason) = match (health, SysMode::get()) {
+        // Boot not Finished, Never Skip to Disarmed from here
+        (_, SysMode::Boot | SysMode::SelfTest | SysMode::Calibrating) => return,
+        (Health::Critical, SysMode::Error | SysMode::Fault) => return,
+        (Health::Critical, _) => (SysMode::Error, Reason::DeviceLost),
+        (Health::Degraded, SysMode::Disarmed | SysMode::Armed) => {
+            (SysMode::Degraded, Reason::DeviceLost)
+        }
+        (Health::Degraded, SysMode::Error) if recoverable => {
+            (SysMode::Degraded, Reason::DeviceRecovered)
+        }
+        (Health::Nominal, SysMode::Error | SysMode::Degraded) if recoverable => {
+            (SysMode::Disarmed, Reason::DeviceRecovered)
+        }
+        _ => return,
+    };
+
+    let _ = mode.enter(reason);
+}
@@ -0,0 +1,111 @@
+//!
+//! # BMI088 Configuration
+//!
+
+use crate::FifoConfig;
+use crate::register::*;
+
+///
+/// # Interrupt Pin Mode
+///
+/// Applies to the gyro INT3 and the accel INT1.
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IntPinMode {
+    /// Active High, otherwise Active Low
+    pub active_high: bool,
+    /// Open-Drain, otherwise Push-Pull
+    pub open_drain: bool,
+}
+
+///
+/// # BMI088 Configuration
+///
+/// The scale factors and the sample period are derived from
+/// the ranges and the data rates, so they can not get out of sync.
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Bmi088Config {
+    pub gyro_range: GyroRange,
+    pub gyro_bandwidth: GyroBandwidth,
+    pub acc_range: AccRange,
+    pub acc_odr: AccOdr,
+    pub acc_bwp: AccBwp,
+    pub int_pin: IntPinMode,
+    /// Accel Data Ready Interrupt on INT1
+    ///
+    /// Hardware data synchronization is not supported, it needs
+    /// the Bosch feature configuration file uploaded first.
+    pub acc_drdy: bool,
+    /// FIFO Mode with Watermark Interrupt, otherwise Data Ready
+    pub fifo: Option<FifoConfig>,
+}
+
+impl Bmi088Config {
+    ///
+    /// # Default Configuration
+    ///
+    /// - Gyro: ±2000dps, ODR=1000Hz, FBW=116Hz
+    /// - Accel: ±12g, ODR=200Hz, Normal
+    /// - Interrupts: Open-Drain, Active Low, Gyro Only
+    /// - FIFO: Disabled
+    ///
+    pub const fn new() -> Self {
+        Self {
+            gyro_range: GyroRange::Dps2000,
+            gyro_bandwidth: GyroBandwidth::Odr1000Fbw116,
+            acc_range: AccRange::G12,
+            acc_odr: AccOdr::Hz200,
+            acc_bwp: AccBwp::Normal,
+            int_pin: IntPinMode {
+                active_high: false,
+                open_drain: true,
+            },
+            acc_drdy: false,
+            fifo: None,
+        }
+    }
+
+    ///
+    /// # Gyro Scale Factor in dps/LSB
+    ///
+    pub const fn gyro_scale(&self) -> f64 {
+        self.gyro_range.scale()
+    }
+
+    ///
+    /// # Accel Scale Factor in mg/LSB
+    ///
+    pub const fn acc_scale(&self) -> f64 {
+        self.acc_range.scale()
+    }
+
+    ///
+    /// # Gyro Output Data Rate in Hz
+    ///
+    pub const fn gyro_odr(&self) -> f64 {
+        self.gyro_bandwidth.odr()
+    }
+
+    ///
+    /// # Gyro Sample Period in s
+    ///
+    pub const fn gyro_period(&self) -> f64 {
+        self.gyro_bandwidth.period()
+    }
+
+    ///
+    /// # Accel Sample Period in s
+    ///
+    pub const fn acc_period(&self) -> f64 {
+        self.acc_odr.period()
+    }
+}
+
+impl Default for Bmi088Config {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,639 @@
+//!
+//! # BMI088 Driver
+//!
+
+use crate::fifo::{self, AccFifo, FifoStats, GyroFifo};
+use crate::register::{self as reg, acc, gyro};
+use crate::{Bmi088Config, Bmi088Error, SelfTestReport};
+use embassy_futures::select::{Either, Either3, select, select3};
+use embedded_hal_async::{delay::DelayNs, digital::Wait, spi::SpiDevice};
+
+const WAIT_IV: u32 = 150; // us
+const WAIT_RESET: u32 = 50; // ms
+const WAIT_DATA: u32 = 10; // ms
+const WAIT_ST_CONF: u32 = 3; // ms
+const WAIT_ST_EXCITE: u32 = 60; // ms
+const WAIT_BIST: u32 = 50; // ms
+
+type Result<T, E> = core::result::Result<T, Bmi088Error<E>>;
+
+///
+/// # BMI088
+///
+/// - `A`: Accel SPI Device
+/// - `G`: Gyro SPI Device
+/// - `I`: Gyro Interrupt Line (INT3), and Accel INT1 if Wired
+/// - `D`: Delay Provider
+///
+/// All transfers go through `buffer`, which must be DMA accessible
+/// when the SPI device uses DMA. FIFO reads are limited by its size,
+/// [`fifo::FIFO_BUFFER`] bytes hold a full FIFO.
+///
+pub struct BMI088<'b, A, G, I, D> {
+    acc: A,
+    gyro: G,
+    gyro_int: I,
+    acc_int: Option<I>,
+    delay: D,
+    buffer: &'b mut [u8],
+    config: Bmi088Config,
+    stats: FifoStats,
+}
+
+impl<'b, A, G, I, D> BMI088<'b, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    pub fn new(
+        acc: A,
+        gyro: G,
+        gyro_int: I,
+        delay: D,
+        buffer: &'b mut [u8],
+        config: Bmi088Config,
+    ) -> Self {
+        if buffer.len() < 8 {
+            panic!("BMI088 Buffer Size MUST be at Least 8 Bytes");
+        }
+
+        Self {
+            acc,
+            gyro,
+            gyro_int,
+            acc_int: None,
+            delay,
+            buffer,
+            config,
+            stats: FifoStats::default(),
+        }
+    }
+
+    ///
+    /// # With Accel Interrupt
+    ///
+    /// Wire the accel INT1 line, used by [`Self::wait_data`]
+    /// when [`Bmi088Config::acc_drdy`] is set.
+    ///
+    pub fn with_acc_int(mut self, acc_int: I) -> Self {
+        self.acc_int = Some(acc_int);
+        self
+    }
+
+    ///
+    /// # Get Configuration
+    ///
+    #[inline]
+    pub fn config(&self) -> &Bmi088Config {
+        &self.config
+    }
+
+    ///
+    /// # Get FIFO Statistics
+    ///
+    #[inline]
+    pub fn fifo_stats(&self) -> &FifoStats {
+        &self.stats
+    }
+
+    ///
+    /// # Release
+    ///
+    /// Destroy the driver and return the devices.
+    ///
+    pub fn release(self) -> (A, G, I, Option<I>, D) {
+        (self.acc, self.gyro, self.gyro_int, self.acc_int, self.delay)
+    }
+}
+
+///
+/// # Data Ready Source
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataReady {
+    /// New Gyro Data
+    Gyro,
+    /// New Accel Data
+    Acc,
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    pub async fn init(&mut self) -> Result<(), A::Error> {
+        self.init_acc().await?;
+        self.delay.delay_us(WAIT_IV).await;
+        self.init_gyro().await?;
+        self.delay.delay_us(WAIT_IV).await;
+        Ok(())
+    }
+
+    ///
+    /// # Configure
+    ///
+    /// Apply a new configuration at runtime without a reset.
+    ///
+    pub async fn configure(&mut self, config: Bmi088Config) -> Result<(), A::Error> {
+        self.config = config;
+        self.config_acc().await?;
+        self.config_gyro().await
+    }
+
+    ///
+    /// # Wait for New Data
+    ///
+    /// Waits for the gyro data ready interrupt, or the
+    /// FIFO watermark interrupt in FIFO mode.
+    ///
+    pub async fn wait_new_data(&mut self) -> Result<(), A::Error> {
+        let wait = match self.config.fifo {
+            // Twice the Watermark Fill Time
+            Some(f) => (f.watermark as f64 * self.config.gyro_period() * 2e3) as u32,
+            None => 0,
+        };
+
+        let timeout = self.delay.delay_ms(WAIT_DATA + wait);
+        let edge = async {
+            match self.config.int_pin.active_high {
+                true => self.gyro_int.wait_for_rising_edge().await,
+                false => self.gyro_int.wait_for_falling_edge().await,
+            }
+        };
+
+        match select(edge, timeout).await {
+            Either::First(Ok(())) => Ok(()),
+            Either::First(Err(_)) => Err(Bmi088Error::Pin),
+            Either::Second(()) => Err(Bmi088Error::Timeout),
+        }
+    }
+
+    ///
+    /// # Wait for Gyro or Accel Data
+    ///
+    /// Waits for either data ready interrupt, the gyro wins when both
+    /// are pending. Falls back to [`Self::wait_new_data`] when the accel
+    /// interrupt is not wired or not enabled.
+    ///
+    pub async fn wait_data(&mut self) -> Result<DataReady, A::Error> {
+        let active_high = self.config.int_pin.active_high;
+        let Some(acc_int) = self.acc_int.as_mut().filter(|_| self.config.acc_drdy) else {
+            return self.wait_new_data().await.map(|()| DataReady::Gyro);
+        };
+
+        let timeout = self.delay.delay_ms(WAIT_DATA);
+        let gyro = async {
+            match active_high {
+                true => self.gyro_int.wait_for_rising_edge().await,
+                false => self.gyro_int.wait_for_falling_edge().await,
+            }
+        };
+        let acc = async {
+            match active_high {
+                true => acc_int.wait_for_rising_edge().await,
+                false => acc_int.wait_for_falling_edge().await,
+            }
+        };
+
+        match select3(gyro, acc, timeout).await {
+            Either3::First(Ok(())) => Ok(DataReady::Gyro),
+            Either3::Second(Ok(())) => Ok(DataReady::Acc),
+            Either3::First(Err(_)) | Either3::Second(Err(_)) => Err(Bmi088Error::Pin),
+            Either3::Third(()) => Err(Bmi088Error::Timeout),
+        }
+    }
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    pub async fn read_gyro(&mut self) -> Result<(f64, f64, f64), A::Error> {
+        // Determined by Register `GYRO_RANGE(0x0F)`
+        let pref = self.config.gyro_scale(); // dps/LSB
+
+        // Read 6 bytes from RATE_X_LSB: 0x02
+        let buf = self.read_gyro_regs(gyro::RATE_X_LSB, 6).await?;
+        Ok((
+            (i16::from_le_bytes([buf[0], buf[1]]) as f64 * pref),
+            (i16::from_le_bytes([buf[2], buf[3]]) as f64 * pref),
+            (i16::from_le_bytes([buf[4], buf[5]]) as f64 * pref),
+        )) // Return in dps
+    }
+
+    pub async fn read_acc(&mut self) -> Result<(f64, f64, f64), A::Error> {
+        // Determined by Register `ACC_RANGE(0x41)`
+        let pref = self.config.acc_scale(); // mg/LSB
+
+        // Read 6 bytes from ACC_X_LSB: 0x12
+        let buf = self.read_acc_regs(acc::X_LSB, 6).await?;
+        Ok((
+            (i16::from_le_bytes([buf[0], buf[1]]) as f64 * pref),
+            (i16::from_le_bytes([buf[2], buf[3]]) as f64 * pref),
+            (i16::from_le_bytes([buf[4], buf[5]]) as f64 * pref),
+        )) // Return in mg
+    }
+
+    /// The temperature sensor data is updated every 1.28s
+    pub async fn read_temp(&mut self) -> Result<f32, A::Error> {
+        // Read 2 bytes from TEMP_MSB: 0x22
+        let buf = self.read_acc_regs(acc::TEMP_MSB, 2).await?;
+
+        let temp = ((buf[0] as i16) << 3) | ((buf[1] as i16) >> 5);
+        let temp = if temp > 0x3FF { temp - 0x800 } else { temp };
+        Ok(temp as f32 * 0.125 + 23.) // in °C
+    }
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    ///
+    /// # Read Gyro FIFO
+    ///
+    /// Reads up to `out.len()` frames in dps, oldest first.
+    /// Frames that do not fit are left in the FIFO.
+    ///
+    pub async fn read_gyro_fifo(&mut self, out: &mut [[f64; 3]]) -> Result<GyroFifo, A::Error> {
+        let pref = self.config.gyro_scale(); // dps/LSB
+
+        let status = self.read_reg_gyro(gyro::FIFO_STATUS).await?;
+        let status = reg::GyroFifoStatus::from_bits(status);
+
+        let cap = (self.buffer.len() - 1) / 6;
+        let n = (status.frames() as usize).min(out.len()).min(cap);
+
+        let frames = match n {
+            0 => 0,
+            n => {
+                // Burst Read FIFO_DATA: 0x3F, 6 Bytes per Frame
+                let buf = self.read_gyro_regs(gyro::FIFO_DATA, n * 6).await?;
+                fifo::parse_gyro_fifo(buf, pref, out)
+            }
+        };
+
+        let overrun = status.overrun();
+        if overrun {
+            // Writing FIFO_CONFIG_1 Clears the Overrun
+            let conf = reg::GyroFifoConfig1::new().with_mode(reg::GyroFifoMode::Stream);
+            self.write_reg_gyro(gyro::FIFO_CONFIG_1, conf.into_bits())
+                .await?;
+            self.stats.gyro_overruns = self.stats.gyro_overruns.saturating_add(1);
+        }
+
+        Ok(GyroFifo { frames, overrun })
+    }
+
+    ///
+    /// # Read Accel FIFO
+    ///
+    /// Reads the whole FIFO and writes the data frames in mg, oldest
+    /// first. Data frames that do not fit `out` are dropped.
+    ///
+    pub async fn read_acc_fifo(&mut self, out: &mut [[f64; 3]]) -> Result<AccFifo, A::Error> {
+        let pref = self.config.acc_scale(); // mg/LSB
+
+        // Read 2 bytes from FIFO_LENGTH_0: 0x24
+        let buf = self.read_acc_regs(acc::FIFO_LENGTH_0, 2).await?;
+        let len = (u16::from_le_bytes([buf[0], buf[1]]) & 0x3FFF) as usize;
+        let len = len.min(self.buffer.len() - 2);
+
+        let res = match len {
+            0 => AccFifo::default(),
+            len => {
+                // Burst Read FIFO_DATA: 0x26
+                let buf = self.read_acc_regs(acc::FIFO_DATA, len).await?;
+                fifo::parse_acc_fifo(buf, pref, out)
+            }
+        };
+
+        let s = &mut self.stats;
+        s.acc_skipped = s.acc_skipped.saturating_add(res.skipped);
+        s.dropped = s.dropped.saturating_add(res.dropped);
+
+        Ok(res)
+    }
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    ///
+    /// # Self-Test
+    ///
+    /// Runs the accel self-test and the gyro built-in self-test, then
+    /// resets the accel and restores the configuration. Must be called
+    /// after [`Self::init`], takes about 200ms.
+    ///
+    pub async fn self_test(&mut self) -> Result<SelfTestReport, A::Error> {
+        let (pos, neg) = self.self_test_acc().await?;
+
+        // A Soft Reset is Required after the Accel Self-Test
+        self.init_acc().await?;
+        self.delay.delay_us(WAIT_IV).await;
+
+        let bist = self.self_test_gyro().await?;
+        Ok(SelfTestReport::new(
+            pos,
+            neg,
+            !bist.bist_fail(),
+            bist.rate_ok(),
+        ))
+    }
+
+    async fn self_test_acc(&mut self) -> Result<([f64; 3], [f64; 3]), A::Error> {
+        let range = reg::AccRange::G24;
+        let conf = reg::AccConf::new()
+            .with_odr(reg::AccOdr::Hz1600)
+            .with_bwp(reg::AccBwp::Normal);
+
+        self.write_verify_acc(acc::RANGE, range.into_bits()).await?;
+        self.write_verify_acc(acc::CONF, conf.into_bits()).await?;
+        self.delay.delay_ms(WAIT_ST_CONF).await;
+
+        let mut res = [[0.; 3]; 2];
+        for (i, excite) in [reg::AccSelfTest::Positive, reg::AccSelfTest::Negative]
+            .into_iter()
+            .enumerate()
+        {
+            self.write_reg_acc(acc::SELF_TEST, excite.into_bits())
+                .await?;
+            self.delay.delay_ms(WAIT_ST_EXCITE).await;
+
+            // Read 6 bytes from ACC_X_LSB: 0x12
+            let buf = self.read_acc_regs(acc::X_LSB, 6).await?;
+            res[i] = core::array::from_fn(|n: _| {
+                i16::from_le_bytes([buf[n * 2], buf[n * 2 + 1]]) as f64 * range.scale()
+            });
+        }
+
+        let off = reg::AccSelfTest::Off.into_bits();
+        self.write_reg_acc(acc::SELF_TEST, off).await?;
+
+        Ok((res[0], res[1])) // Return in mg
+    }
+
+    async fn self_test_gyro(&mut self) -> Result<reg::GyroSelfTest, A::Error> {
+        let trig = reg::GyroSelfTest::new().with_trig_bist(true);
+        self.write_reg_gyro(gyro::SELF_TEST, trig.into_bits())
+            .await?;
+
+        for _ in 0..WAIT_BIST {
+            self.delay.delay_ms(1).await;
+            let bist = self.read_reg_gyro(gyro::SELF_TEST).await?;
+            let bist = reg::GyroSelfTest::from_bits(bist);
+            if bist.bist_rdy() {
+                return Ok(bist);
+            }
+        }
+
+        Err(Bmi088Error::Timeout)
+    }
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    async fn init_gyro(&mut self) -> Result<(), A::Error> {
+        // Read GYRO_CHIP_ID: 0x0F
+        self.read_reg_gyro(gyro::CHIP_ID).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        // GYRO_SOFTRESET: Write 0xB6 to Reset
+        self.write_reg_gyro(gyro::SOFTRESET, reg::SOFTRESET_CMD)
+            .await?;
+        self.delay.delay_ms(WAIT_RESET).await;
+        // Read GYRO_CHIP_ID: 0x0F
+        self.read_reg_gyro(gyro::CHIP_ID).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        match self.read_reg_gyro(gyro::CHIP_ID).await? {
+            gyro::ID => {}
+            id => return Err(Bmi088Error::GyroChipId(id)),
+        }
+
+        self.config_gyro().await
+    }
+
+    async fn config_gyro(&mut self) -> Result<(), A::Error> {
+        let c = self.config;
+
+        let fifo = c.fifo.is_some();
+        let bandwidth = reg::GyroBandwidthReg::new().with_bandwidth(c.gyro_bandwidth);
+        let int_ctrl = reg::GyroIntCtrl::new()
+            .with_data_en(!fifo)
+            .with_fifo_en(fifo);
+        let int_conf = reg::GyroIntIoConf::new()
+            .with_int3_lvl(c.int_pin.active_high)
+            .with_int3_od(c.int_pin.open_drain);
+        let int_map = reg::GyroIntMap::new()
+            .with_int3_data(!fifo)
+            .with_int3_fifo(fifo);
+
+        if let Some(f) = c.fifo {
+            let conf = reg::GyroFifoConfig1::new().with_mode(reg::GyroFifoMode::Stream);
+
+            for (reg, val) in [
+                (gyro::FIFO_CONFIG_1, conf.into_bits()), // Stream Mode, X, Y and Z
+                (gyro::FIFO_CONFIG_0, f.watermark & 0x7F), // Watermark in Frames
+                (gyro::FIFO_WM_ENABLE, 0x88),            // Enable Watermark Interrupt
+            ] {
+                self.write_verify_gyro(reg, val).await?;
+            }
+        }
+
+        for (reg, val) in [
+            (gyro::RANGE, c.gyro_range.into_bits()),         // GYRO_RANGE
+            (gyro::BANDWIDTH, bandwidth.into_bits()),        // GYRO_BANDWIDTH: ODR, FBW
+            (gyro::LPM1, 0x00),                              // GYRO_LPM1: Normal mode
+            (gyro::INT_CTRL, int_ctrl.into_bits()),          // GYRO_INT_CTRL: New Data or FIFO
+            (gyro::INT3_INT4_IO_CONF, int_conf.into_bits()), // INT3 Pin Mode
+            (gyro::INT3_INT4_IO_MAP, int_map.into_bits()),   // Data Ready or FIFO to INT3
+        ] {
+            self.write_verify_gyro(reg, val).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn init_acc(&mut self) -> Result<(), A::Error> {
+        // Read ACC_CHIP_ID: 0x1E
+        self.read_reg_acc(acc::CHIP_ID).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        // ACC_SOFTRESET: Write 0xB6 to Reset
+        self.write_reg_acc(acc::SOFTRESET, reg::SOFTRESET_CMD)
+            .await?;
+        self.delay.delay_ms(WAIT_RESET).await;
+        // Read ACC_CHIP_ID: 0x1E
+        self.read_reg_acc(acc::CHIP_ID).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        match self.read_reg_acc(acc::CHIP_ID).await? {
+            acc::ID => {}
+            id => return Err(Bmi088Error::AccChipId(id)),
+        }
+
+        self.config_acc().await
+    }
+
+    async fn config_acc(&mut self) -> Result<(), A::Error> {
+        let c = self.config;
+
+        let conf = reg::AccConf::new().with_odr(c.acc_odr).with_bwp(c.acc_bwp);
+
+        for (reg, val) in [
+            (acc::PWR_CTRL, 0x04),                 // ACC_PWR_CTRL: Enable Accelerometer
+            (acc::PWR_CONF, 0x00),                 // ACC_PWR_CONF: Active Mode
+            (acc::CONF, conf.into_bits()),         // ACC_CONF: ODR, OSR
+            (acc::RANGE, c.acc_range.into_bits()), // ACC_RANGE
+        ] {
+            self.write_verify_acc(reg, val).await?;
+        }
+
+        if c.acc_drdy {
+            let int_conf = reg::AccInt1IoConf::new()
+                .with_out(true)
+                .with_lvl(c.int_pin.active_high)
+                .with_od(c.int_pin.open_drain);
+            let int_map = reg::AccIntMap::new().with_int1_drdy(true);
+
+            for (reg, val) in [
+                (acc::INT1_IO_CONF, int_conf.into_bits()), // INT1 Pin Mode
+                (acc::INT1_INT2_MAP_DATA, int_map.into_bits()), // Data Ready to INT1
+            ] {
+                self.write_verify_acc(reg, val).await?;
+            }
+        }
+
+        if c.fifo.is_some() {
+            let conf = reg::AccFifoConfig1::new().with_acc_en(true);
+
+            for (reg, val) in [
+                (acc::FIFO_DOWNS, 0x80),                // No Downsampling
+                (acc::FIFO_CONFIG_0, 0x02),             // Stream Mode
+                (acc::FIFO_CONFIG_1, conf.into_bits()), // Store Accel Data
+            ] {
+                self.write_verify_acc(reg, val).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<A, G, I, D> BMI088<'_, A, G, I, D>
+where
+    A: SpiDevice,
+    G: SpiDevice<Error = A::Error>,
+    I: Wait,
+    D: DelayNs,
+{
+    ///
+    /// # Burst Read Gyro Registers
+    ///
+    /// Frame: `[reg | 0x80, data...]`
+    ///
+    async fn read_gyro_regs(&mut self, reg: u8, len: usize) -> Result<&[u8], A::Error> {
+        let buf = &mut self.buffer[..len + 1];
+        buf[0] = reg | 0x80;
+        buf[1..].fill(0xFF);
+
+        let res = self.gyro.transfer_in_place(buf).await;
+        res.map_err(Bmi088Error::Spi)?;
+        Ok(&buf[1..])
+    }
+
+    ///
+    /// # Burst Read Accel Registers
+    ///
+    /// Frame: `[reg | 0x80, dummy, data...]`, the accel
+    /// returns one dummy byte before the register data.
+    ///
+    async fn read_acc_regs(&mut self, reg: u8, len: usize) -> Result<&[u8], A::Error> {
+        let buf = &mut self.buffer[..len + 2];
+        buf[0] = reg | 0x80;
+        buf[1..].fill(0xFF);
+
+        let res = self.acc.transfer_in_place(buf).await;
+        res.map_err(Bmi088Error::Spi)?;
+        Ok(&buf[2..])
+    }
+
+    ///
+    /// # Write and Verify Gyro Register
+    ///
+    async fn write_verify_gyro(&mut self, reg: u8, val: u8) -> Result<(), A::Error> {
+        self.delay.delay_us(WAIT_IV).await;
+        self.write_reg_gyro(reg, val).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        match self.read_reg_gyro(reg).await? {
+            x if x == val => Ok(()),
+            actual => Err(Bmi088Error::Readback {
+                reg,
+                expected: val,
+                actual,
+            }),
+        }
+    }
+
+    ///
+    /// # Write and Verify Accel Register
+    ///
+    async fn write_verify_acc(&mut self, reg: u8, val: u8) -> Result<(), A::Error> {
+        self.delay.delay_us(WAIT_IV).await;
+        self.write_reg_acc(reg, val).await?;
+        self.delay.delay_us(WAIT_IV).await;
+        match self.read_reg_acc(reg).await? {
+            x if x == val => Ok(()),
+            actual => Err(Bmi088Error::Readback {
+                reg,
+                expected: val,
+                actual,
+            }),
+        }
+    }
+
+    async fn read_reg_gyro(&mut self, reg: u8) -> Result<u8, A::Error> {
+        Ok(self.read_gyro_regs(reg, 1).await?[0])
+    }
+
+    async fn write_reg_gyro(&mut self, reg: u8, val: u8) -> Result<(), A::Error> {
+        let buf = &mut self.buffer[..2];
+        buf[0] = reg & 0x7F;
+        buf[1] = val;
+
+        let res = self.gyro.write(buf).await;
+        res.map_err(Bmi088Error::Spi)
+    }
+
+    async fn read_reg_acc(&mut self, reg: u8) -> Result<u8, A::Error> {
+        Ok(self.read_acc_regs(reg, 1).await?[0])
+    }
+
+    async fn write_reg_acc(&mut self, reg: u8, val: u8) -> Result<(), A::Error> {
+        let buf = &mut self.buffer[..2];
+        buf[0] = reg & 0x7F;
+        buf[1] = val;
+
+        let res = self.acc.write(buf).await;
+        res.map_err(Bmi088Error::Spi)
+    }
+}
@@ -0,0 +1,25 @@
+//!
+//! # BMI088 Error
+//!
+
+///
+/// # BMI088 Error
+///
+/// `E` is the error type of the underlying SPI device.
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bmi088Error<E> {
+    /// SPI / DMA Transfer Failed
+    Spi(E),
+    /// Interrupt Pin Failed
+    Pin,
+    /// Unexpected `ACC_CHIP_ID`, Expected `0x1E`
+    AccChipId(u8),
+    /// Unexpected `GYRO_CHIP_ID`, Expected `0x0F`
+    GyroChipId(u8),
+    /// Register Readback Mismatch after Write
+    Readback { reg: u8, expected: u8, actual: u8 },
+    /// No Data Ready Interrupt in Time, or Self-Test not Finished
+    Timeout,
+}
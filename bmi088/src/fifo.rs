@@ -0,0 +1,144 @@
+//!
+//! # BMI088 FIFO
+//!
+//! The gyro FIFO stores headerless 6 byte frames, the accel FIFO
+//! stores frames with a header byte (data, skip, sensortime, ...),
+//! see the BMI088 datasheet chapter 4.9 (FIFO).
+//!
+
+/// Gyro FIFO Capacity in Frames
+pub const GYRO_FIFO_FRAMES: usize = 100;
+/// Accel FIFO Capacity in Bytes
+pub const ACC_FIFO_BYTES: usize = 1024;
+/// Buffer Size for Burst Reading a Full FIFO
+pub const FIFO_BUFFER: usize = ACC_FIFO_BYTES + 2;
+
+///
+/// # FIFO Configuration
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoConfig {
+    /// Gyro Frames per Watermark Interrupt: 1 ~ 99
+    pub watermark: u8,
+}
+
+///
+/// # FIFO Statistics
+///
+/// Accumulated since the driver was created.
+///
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoStats {
+    /// Gyro FIFO Overruns, Frames were Lost
+    pub gyro_overruns: u32,
+    /// Accel Frames Skipped by the Sensor on Overflow
+    pub acc_skipped: u32,
+    /// Frames Read but Dropped for Lack of Space
+    pub dropped: u32,
+}
+
+///
+/// # Gyro FIFO Read Result
+///
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GyroFifo {
+    /// Frames Written to the Output
+    pub frames: usize,
+    /// FIFO Overrun before this Read
+    pub overrun: bool,
+}
+
+///
+/// # Accel FIFO Read Result
+///
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccFifo {
+    /// Frames Written to the Output
+    pub frames: usize,
+    /// Frames Skipped by the Sensor
+    pub skipped: u32,
+    /// Frames Dropped for Lack of Space
+    pub dropped: u32,
+    /// Last Sensortime, in 39.0625us Ticks
+    pub sensortime: Option<u32>,
+}
+
+/// Accel Data Frame Header, Lower 2 Bits are Interrupt Tags
+const HEADER_ACC: u8 = 0x84;
+/// Skip Frame Header
+const HEADER_SKIP: u8 = 0x40;
+/// Sensortime Frame Header
+const HEADER_TIME: u8 = 0x44;
+/// FIFO Input Config Frame Header
+const HEADER_CONFIG: u8 = 0x48;
+/// Sample Drop Frame Header
+const HEADER_DROP: u8 = 0x50;
+
+///
+/// # Parse Gyro FIFO Frames
+///
+/// Converts raw frames with `scale` in unit/LSB.
+///
+pub fn parse_gyro_fifo(data: &[u8], scale: f64, out: &mut [[f64; 3]]) -> usize {
+    let mut n = 0;
+    for (frame, o) in data.as_chunks::<6>().0.iter().zip(out.iter_mut()) {
+        *o = axes(frame, scale);
+        n += 1;
+    }
+    n
+}
+
+///
+/// # Parse Accel FIFO Frames
+///
+/// Converts data frames with `scale` in unit/LSB, stops at the first
+/// empty (`0x80`), unknown or truncated frame.
+///
+pub fn parse_acc_fifo(data: &[u8], scale: f64, out: &mut [[f64; 3]]) -> AccFifo {
+    let mut res = AccFifo::default();
+    let mut i = 0;
+
+    while let Some(&header) = data.get(i) {
+        let len = match header {
+            h if h & 0xFC == HEADER_ACC => 6,
+            HEADER_SKIP | HEADER_CONFIG | HEADER_DROP => 1,
+            HEADER_TIME => 3,
+            _ => break, // Empty or Invalid
+        };
+
+        let Some(payload) = data.get(i + 1..i + 1 + len) else {
+            break; // Truncated
+        };
+        i += 1 + len;
+
+        match header {
+            h if h & 0xFC == HEADER_ACC => match out.get_mut(res.frames) {
+                Some(o) => {
+                    *o = axes(payload, scale);
+                    res.frames += 1;
+                }
+                None => res.dropped += 1,
+            },
+            HEADER_SKIP => res.skipped += payload[0] as u32,
+            HEADER_TIME => {
+                let t = u32::from_le_bytes([payload[0], payload[1], payload[2], 0]);
+                res.sensortime = Some(t);
+            }
+            _ => {}
+        }
+    }
+
+    res
+}
+
+fn axes(frame: &[u8], scale: f64) -> [f64; 3] {
+    [
+        i16::from_le_bytes([frame[0], frame[1]]) as f64 * scale,
+        i16::from_le_bytes([frame[2], frame[3]]) as f64 * scale,
+        i16::from_le_bytes([frame[4], frame[5]]) as f64 * scale,
+    ]
+}
@@ -0,0 +1,172 @@
+//!
+//! # Heater Control
+//!
+//! Constant temperature control of the IMU from its own temperature
+//! sensor. A clamped PID yields the heater duty cycle, driving the
+//! heater itself is left to the caller.
+//!
+//! Both the over-temperature cutoff and the warm-up timeout latch:
+//! a heater that could not settle in time is not trusted to be stable
+//! later, the firmware reports it as a hardware error.
+//!
+
+///
+/// # Heater Configuration
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HeatConfig {
+    /// Target Temperature in °C
+    pub setpoint: f32,
+    /// Over-Temperature Cutoff in °C
+    pub cutoff: f32,
+    /// Control Period in Gyro Samples
+    pub period: u32,
+    /// Control Period in s
+    pub dt: f32,
+
+    /// Proportional Gain in 1/°C
+    pub kp: f32,
+    /// Integral Gain in 1/(°C·s)
+    pub ki: f32,
+    /// Derivative Gain in s/°C
+    pub kd: f32,
+    /// Maximum Duty Cycle: 0.0 ~ 1.0
+    pub duty_max: f32,
+
+    /// Stable Band around the Setpoint in °C
+    pub band: f32,
+    /// Updates within the Band before Stable
+    pub settle: u32,
+    /// Updates before the Warm-Up Times Out
+    pub timeout: u32,
+    /// Control Periods without a Temperature before the Heater is Cut
+    pub stale: u32,
+}
+
+///
+/// # Heater State
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HeatState {
+    /// Temperature not yet settled, the AHRS must wait
+    WarmingUp,
+    /// Not settled within the timeout, latched, still regulating
+    TimedOut,
+    /// Temperature settled at the setpoint
+    Stable,
+    /// Cutoff exceeded, heater latched off
+    OverTemp,
+}
+
+///
+/// # PID Controller
+///
+/// Output clamped to `0 ~ duty_max`, the integral only
+/// accumulates while the output is not saturated (anti-windup).
+///
+struct Pid {
+    integral: f32,
+    last_err: Option<f32>,
+}
+
+impl Pid {
+    const fn new() -> Self {
+        Self {
+            integral: 0.,
+            last_err: None,
+        }
+    }
+
+    fn update(&mut self, c: &HeatConfig, err: f32) -> f32 {
+        let derive = match self.last_err {
+            Some(last) => (err - last) / c.dt,
+            None => 0.,
+        };
+        self.last_err = Some(err);
+
+        let integral = self.integral + err * c.dt;
+        let out = c.kp * err + c.ki * integral + c.kd * derive;
+
+        // Anti-Windup: Integrate only when Unsaturated,
+        // or when the Error drives the Output back into Range.
+        let saturated = (out > c.duty_max && err > 0.) || (out < 0. && err < 0.);
+        if !saturated {
+            self.integral = integral;
+        }
+
+        out.clamp(0., c.duty_max)
+    }
+}
+
+///
+/// # Heater Controller
+///
+pub struct HeatControl {
+    config: HeatConfig,
+    pid: Pid,
+    state: HeatState,
+    settled: u32,
+    updates: u32,
+}
+
+impl HeatControl {
+    pub const fn new(config: HeatConfig) -> Self {
+        Self {
+            config,
+            pid: Pid::new(),
+            state: HeatState::WarmingUp,
+            settled: 0,
+            updates: 0,
+        }
+    }
+
+    ///
+    /// # Get Configuration
+    ///
+    #[inline]
+    pub fn config(&self) -> &HeatConfig {
+        &self.config
+    }
+
+    ///
+    /// # Get Heater State
+    ///
+    #[inline]
+    pub fn state(&self) -> HeatState {
+        self.state
+    }
+
+    ///
+    /// # Control Step
+    ///
+    /// Run one control step with the measured temperature in °C.
+    ///
+    /// Returns the duty cycle, `None` once the heater must stay off.
+    ///
+    pub fn update(&mut self, temp: f32) -> Option<f32> {
+        let c = &self.config;
+        if self.state == HeatState::OverTemp || temp > c.cutoff {
+            self.state = HeatState::OverTemp;
+            return None;
+        }
+
+        let err = c.setpoint - temp;
+        let duty = self.pid.update(c, err);
+
+        self.settled = match err.abs() < c.band {
+            true => self.settled.saturating_add(1),
+            false => 0,
+        };
+
+        self.updates = self.updates.saturating_add(1);
+        self.state = match self.state {
+            HeatState::WarmingUp if self.settled >= c.settle => HeatState::Stable,
+            HeatState::WarmingUp if self.updates >= c.timeout => HeatState::TimedOut,
+            state => state,
+        };
+
+        Some(duty)
+    }
+}
@@ -0,0 +1,33 @@
+//!
+//! # BMI088 Driver
+//!
+//! Hardware independent register level driver for the Bosch BMI088,
+//! generic over [`SpiDevice`] for the accel and gyro chip selects,
+//! [`Wait`] for the interrupt lines and [`DelayNs`] for the timing.
+//!
+//! [`SpiDevice`]: embedded_hal_async::spi::SpiDevice
+//! [`Wait`]: embedded_hal_async::digital::Wait
+//! [`DelayNs`]: embedded_hal_async::delay::DelayNs
+//!
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod fifo;
+pub mod heat;
+pub mod monitor;
+pub mod register;
+pub mod selftest;
+
+mod config;
+mod driver;
+mod error;
+
+#[cfg(test)]
+mod tests;
+
+pub use config::{Bmi088Config, IntPinMode};
+pub use driver::{BMI088, DataReady};
+pub use error::Bmi088Error;
+pub use fifo::{FifoConfig, FifoStats};
+pub use monitor::{Faults, Monitor, MonitorLimits, MonitorReport};
+pub use selftest::SelfTestReport;
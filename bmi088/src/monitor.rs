@@ -0,0 +1,226 @@
+//!
+//! # Sensor Fault Monitor
+//!
+//! Watches the scaled readings for signs of bad data:
+//!
+//! - Clipping: a reading at the full scale of its range.
+//! - Stuck: the same reading repeated, all three axes identical.
+//! - Norm: the mean accel norm far from 1g for several windows.
+//!
+//! Accel samples are grouped into windows, each closing with a
+//! [`MonitorReport`] that also carries the vibration RMS per axis.
+//!
+
+use crate::Bmi088Config;
+
+///
+/// # Monitor Limits
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MonitorLimits {
+    /// Accel Samples per Window
+    pub window: u32,
+    /// Identical Consecutive Samples before Stuck
+    pub stuck: u32,
+    /// Clipped Fraction of a Window before Faulty
+    pub clip_ratio: f64,
+    /// Allowed Deviation of the Mean Accel Norm from 1g, in g
+    pub norm_tol: f64,
+    /// Consecutive Windows off Norm before Faulty
+    pub norm_windows: u32,
+}
+
+impl MonitorLimits {
+    /// 1s Windows at the Default Accel Rate, 5s off Norm
+    pub const DEFAULT: Self = Self {
+        window: Bmi088Config::new().acc_odr.odr() as u32,
+        stuck: 50,
+        clip_ratio: 0.05,
+        norm_tol: 0.3,
+        norm_windows: 5,
+    };
+}
+
+///
+/// # Active Faults
+///
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Faults {
+    pub gyro_stuck: bool,
+    pub acc_stuck: bool,
+    pub gyro_clipping: bool,
+    pub acc_clipping: bool,
+    pub acc_norm: bool,
+}
+
+impl Faults {
+    ///
+    /// # Any Fault Active
+    ///
+    pub fn any(&self) -> bool {
+        self.gyro_stuck
+            || self.acc_stuck
+            || self.gyro_clipping
+            || self.acc_clipping
+            || self.acc_norm
+    }
+}
+
+///
+/// # Window Report
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MonitorReport {
+    /// Accel Vibration RMS per Axis in g, Mean Removed
+    pub vibration: [f64; 3],
+    /// Mean Accel Norm in g
+    pub norm: f64,
+    /// Clipped Gyro Samples in the Window
+    pub gyro_clips: u32,
+    /// Clipped Accel Samples in the Window
+    pub acc_clips: u32,
+    /// Faults at the End of the Window
+    pub faults: Faults,
+}
+
+///
+/// # Repeated Reading Counter
+///
+#[derive(Default)]
+struct Repeat {
+    last: [f64; 3],
+    count: u32,
+}
+
+impl Repeat {
+    fn push(&mut self, v: [f64; 3]) -> u32 {
+        match v == self.last {
+            true => self.count = self.count.saturating_add(1),
+            false => (self.last, self.count) = (v, 0),
+        }
+        self.count
+    }
+}
+
+pub struct Monitor {
+    limits: MonitorLimits,
+    gyro_full: f64,
+    acc_full: f64,
+
+    gyro_repeat: Repeat,
+    acc_repeat: Repeat,
+
+    n: u32,
+    mean: [f64; 3],
+    m2: [f64; 3],
+    norm: f64,
+    gyro_n: u32,
+    gyro_clips: u32,
+    acc_clips: u32,
+    off_norm: u32,
+
+    faults: Faults,
+}
+
+impl Monitor {
+    pub fn new(config: &Bmi088Config, limits: MonitorLimits) -> Self {
+        // Half an LSB below the Largest Code
+        let full = |scale: f64| (i16::MAX as f64 - 0.5) * scale;
+
+        Self {
+            limits,
+            gyro_full: full(config.gyro_scale()),
+            acc_full: full(config.acc_scale()),
+            gyro_repeat: Repeat::default(),
+            acc_repeat: Repeat::default(),
+            n: 0,
+            mean: [0.; 3],
+            m2: [0.; 3],
+            norm: 0.,
+            gyro_n: 0,
+            gyro_clips: 0,
+            acc_clips: 0,
+            off_norm: 0,
+            faults: Faults::default(),
+        }
+    }
+
+    ///
+    /// # Get Active Faults
+    ///
+    pub fn faults(&self) -> Faults {
+        self.faults
+    }
+
+    ///
+    /// # Push Gyro Sample in dps
+    ///
+    pub fn gyro(&mut self, dps: [f64; 3]) {
+        self.gyro_n += 1;
+        if dps.iter().any(|x: _| x.abs() >= self.gyro_full) {
+            self.gyro_clips += 1;
+        }
+
+        self.faults.gyro_stuck = self.gyro_repeat.push(dps) >= self.limits.stuck;
+    }
+
+    ///
+    /// # Push Accel Sample in mg
+    ///
+    /// Returns the report when the window closes.
+    ///
+    pub fn acc(&mut self, mg: [f64; 3]) -> Option<MonitorReport> {
+        if mg.iter().any(|x: _| x.abs() >= self.acc_full) {
+            self.acc_clips += 1;
+        }
+
+        self.faults.acc_stuck = self.acc_repeat.push(mg) >= self.limits.stuck;
+
+        let g = mg.map(|x: _| x / 1000.);
+        self.n += 1;
+        for ((mean, m2), x) in self.mean.iter_mut().zip(&mut self.m2).zip(g) {
+            let delta = x - *mean;
+            *mean += delta / self.n as f64;
+            *m2 += delta * (x - *mean);
+        }
+        self.norm += libm::sqrt(g.iter().map(|x: _| x * x).sum());
+
+        match self.n >= self.limits.window {
+            true => Some(self.close()),
+            false => None,
+        }
+    }
+
+    fn close(&mut self) -> MonitorReport {
+        let n = self.n as f64;
+        let norm = self.norm / n;
+        let vibration = self.m2.map(|m: _| libm::sqrt(m / n));
+
+        self.off_norm = match (norm - 1.).abs() > self.limits.norm_tol {
+            true => self.off_norm.saturating_add(1),
+            false => 0,
+        };
+
+        let ratio = self.limits.clip_ratio;
+        let f = &mut self.faults;
+        f.acc_clipping = self.acc_clips as f64 > ratio * n;
+        f.gyro_clipping = self.gyro_clips as f64 > ratio * self.gyro_n.max(1) as f64;
+        f.acc_norm = self.off_norm >= self.limits.norm_windows;
+
+        let report = MonitorReport {
+            vibration,
+            norm,
+            gyro_clips: self.gyro_clips,
+            acc_clips: self.acc_clips,
+            faults: self.faults,
+        };
+
+        (self.n, self.mean, self.m2, self.norm) = (0, [0.; 3], [0.; 3], 0.);
+        (self.gyro_n, self.gyro_clips, self.acc_clips) = (0, 0, 0);
+
+        report
+    }
+}
@@ -0,0 +1,459 @@
+//!
+//! # BMI088 Registers
+//!
+//! Addresses and typed register definitions, see the BMI088 datasheet
+//! chapter 5 (Register Description).
+//!
+
+use bitfield_struct::{bitenum, bitfield};
+
+///
+/// # Accel Register Addresses
+///
+pub mod acc {
+    pub const CHIP_ID: u8 = 0x00;
+    pub const X_LSB: u8 = 0x12;
+    pub const TEMP_MSB: u8 = 0x22;
+    pub const FIFO_LENGTH_0: u8 = 0x24;
+    pub const FIFO_DATA: u8 = 0x26;
+    pub const CONF: u8 = 0x40;
+    pub const RANGE: u8 = 0x41;
+    pub const FIFO_DOWNS: u8 = 0x45;
+    pub const FIFO_CONFIG_0: u8 = 0x48;
+    pub const FIFO_CONFIG_1: u8 = 0x49;
+    pub const INT1_IO_CONF: u8 = 0x53;
+    pub const INT1_INT2_MAP_DATA: u8 = 0x58;
+    pub const SELF_TEST: u8 = 0x6D;
+    pub const PWR_CONF: u8 = 0x7C;
+    pub const PWR_CTRL: u8 = 0x7D;
+    pub const SOFTRESET: u8 = 0x7E;
+
+    /// Expected `CHIP_ID`
+    pub const ID: u8 = 0x1E;
+}
+
+///
+/// # Gyro Register Addresses
+///
+pub mod gyro {
+    pub const CHIP_ID: u8 = 0x00;
+    pub const RATE_X_LSB: u8 = 0x02;
+    pub const RANGE: u8 = 0x0F;
+    pub const BANDWIDTH: u8 = 0x10;
+    pub const LPM1: u8 = 0x11;
+    pub const SOFTRESET: u8 = 0x14;
+    pub const INT_CTRL: u8 = 0x15;
+    pub const INT3_INT4_IO_CONF: u8 = 0x16;
+    pub const INT3_INT4_IO_MAP: u8 = 0x18;
+    pub const FIFO_STATUS: u8 = 0x0E;
+    pub const FIFO_WM_ENABLE: u8 = 0x1E;
+    pub const SELF_TEST: u8 = 0x3C;
+    pub const FIFO_CONFIG_0: u8 = 0x3D;
+    pub const FIFO_CONFIG_1: u8 = 0x3E;
+    pub const FIFO_DATA: u8 = 0x3F;
+
+    /// Expected `CHIP_ID`
+    pub const ID: u8 = 0x0F;
+}
+
+/// Soft Reset Command for `SOFTRESET`
+pub const SOFTRESET_CMD: u8 = 0xB6;
+
+///
+/// # Gyro Range: `GYRO_RANGE(0x0F)`
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GyroRange {
+    #[fallback]
+    Dps2000 = 0x00,
+    Dps1000 = 0x01,
+    Dps500 = 0x02,
+    Dps250 = 0x03,
+    Dps125 = 0x04,
+}
+
+impl GyroRange {
+    ///
+    /// # Full Scale in dps
+    ///
+    pub const fn dps(self) -> f64 {
+        match self {
+            Self::Dps2000 => 2000.,
+            Self::Dps1000 => 1000.,
+            Self::Dps500 => 500.,
+            Self::Dps250 => 250.,
+            Self::Dps125 => 125.,
+        }
+    }
+
+    ///
+    /// # Scale Factor in dps/LSB
+    ///
+    pub const fn scale(self) -> f64 {
+        self.dps() / 32768.
+    }
+}
+
+///
+/// # Gyro Output Data Rate and Filter Bandwidth
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GyroBandwidth {
+    /// ODR=2000Hz, FBW=532Hz
+    #[fallback]
+    Odr2000Fbw532 = 0x00,
+    /// ODR=2000Hz, FBW=230Hz
+    Odr2000Fbw230 = 0x01,
+    /// ODR=1000Hz, FBW=116Hz
+    Odr1000Fbw116 = 0x02,
+    /// ODR=400Hz, FBW=47Hz
+    Odr400Fbw47 = 0x03,
+    /// ODR=200Hz, FBW=23Hz
+    Odr200Fbw23 = 0x04,
+    /// ODR=100Hz, FBW=12Hz
+    Odr100Fbw12 = 0x05,
+    /// ODR=200Hz, FBW=64Hz
+    Odr200Fbw64 = 0x06,
+    /// ODR=100Hz, FBW=32Hz
+    Odr100Fbw32 = 0x07,
+}
+
+impl GyroBandwidth {
+    ///
+    /// # Output Data Rate in Hz
+    ///
+    pub const fn odr(self) -> f64 {
+        match self {
+            Self::Odr2000Fbw532 | Self::Odr2000Fbw230 => 2000.,
+            Self::Odr1000Fbw116 => 1000.,
+            Self::Odr400Fbw47 => 400.,
+            Self::Odr200Fbw23 | Self::Odr200Fbw64 => 200.,
+            Self::Odr100Fbw12 | Self::Odr100Fbw32 => 100.,
+        }
+    }
+
+    ///
+    /// # Sample Period in s
+    ///
+    pub const fn period(self) -> f64 {
+        1. / self.odr()
+    }
+}
+
+///
+/// # `GYRO_BANDWIDTH(0x10)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroBandwidthReg {
+    #[bits(7)]
+    pub bandwidth: GyroBandwidth,
+    /// Always Reads as `1`
+    #[bits(1, default = true)]
+    pub fixed: bool,
+}
+
+///
+/// # `GYRO_INT_CTRL(0x15)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroIntCtrl {
+    #[bits(6)]
+    __: u8,
+    /// FIFO Interrupt
+    pub fifo_en: bool,
+    /// New Data Interrupt
+    pub data_en: bool,
+}
+
+///
+/// # `INT3_INT4_IO_CONF(0x16)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroIntIoConf {
+    /// INT3 Active High
+    pub int3_lvl: bool,
+    /// INT3 Open-Drain
+    pub int3_od: bool,
+    /// INT4 Active High
+    pub int4_lvl: bool,
+    /// INT4 Open-Drain
+    pub int4_od: bool,
+    #[bits(4)]
+    __: u8,
+}
+
+///
+/// # `INT3_INT4_IO_MAP(0x18)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroIntMap {
+    /// Data Ready to INT3
+    pub int3_data: bool,
+    __: bool,
+    /// FIFO to INT3
+    pub int3_fifo: bool,
+    #[bits(2)]
+    __: u8,
+    /// FIFO to INT4
+    pub int4_fifo: bool,
+    __: bool,
+    /// Data Ready to INT4
+    pub int4_data: bool,
+}
+
+///
+/// # Accel Range: `ACC_RANGE(0x41)`
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccRange {
+    G3 = 0x00,
+    G6 = 0x01,
+    #[fallback]
+    G12 = 0x02,
+    G24 = 0x03,
+}
+
+impl AccRange {
+    ///
+    /// # Full Scale in mg
+    ///
+    pub const fn mg(self) -> f64 {
+        match self {
+            Self::G3 => 3000.,
+            Self::G6 => 6000.,
+            Self::G12 => 12000.,
+            Self::G24 => 24000.,
+        }
+    }
+
+    ///
+    /// # Scale Factor in mg/LSB
+    ///
+    pub const fn scale(self) -> f64 {
+        self.mg() / 32768.
+    }
+}
+
+///
+/// # Accel Output Data Rate
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccOdr {
+    Hz12_5 = 0x05,
+    Hz25 = 0x06,
+    Hz50 = 0x07,
+    Hz100 = 0x08,
+    #[fallback]
+    Hz200 = 0x09,
+    Hz400 = 0x0A,
+    Hz800 = 0x0B,
+    Hz1600 = 0x0C,
+}
+
+impl AccOdr {
+    ///
+    /// # Output Data Rate in Hz
+    ///
+    pub const fn odr(self) -> f64 {
+        match self {
+            Self::Hz12_5 => 12.5,
+            Self::Hz25 => 25.,
+            Self::Hz50 => 50.,
+            Self::Hz100 => 100.,
+            Self::Hz200 => 200.,
+            Self::Hz400 => 400.,
+            Self::Hz800 => 800.,
+            Self::Hz1600 => 1600.,
+        }
+    }
+
+    ///
+    /// # Sample Period in s
+    ///
+    pub const fn period(self) -> f64 {
+        1. / self.odr()
+    }
+}
+
+///
+/// # Accel Bandwidth Parameter: Oversampling
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccBwp {
+    /// 4-fold Oversampling
+    Osr4 = 0x08,
+    /// 2-fold Oversampling
+    Osr2 = 0x09,
+    /// No Oversampling
+    #[fallback]
+    Normal = 0x0A,
+}
+
+///
+/// # `ACC_CONF(0x40)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct AccConf {
+    #[bits(4)]
+    pub odr: AccOdr,
+    #[bits(4)]
+    pub bwp: AccBwp,
+}
+
+///
+/// # `INT1_IO_CONF(0x53)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct AccInt1IoConf {
+    __: bool,
+    /// Active High
+    pub lvl: bool,
+    /// Open-Drain
+    pub od: bool,
+    /// Output Enable
+    pub out: bool,
+    /// Input Enable
+    pub input: bool,
+    #[bits(3)]
+    __: u8,
+}
+
+///
+/// # `INT1_INT2_MAP_DATA(0x58)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct AccIntMap {
+    /// FIFO Full to INT1
+    pub int1_fful: bool,
+    /// FIFO Watermark to INT1
+    pub int1_fwm: bool,
+    /// Data Ready to INT1
+    pub int1_drdy: bool,
+    __: bool,
+    /// FIFO Full to INT2
+    pub int2_fful: bool,
+    /// FIFO Watermark to INT2
+    pub int2_fwm: bool,
+    /// Data Ready to INT2
+    pub int2_drdy: bool,
+    __: bool,
+}
+
+///
+/// # `FIFO_STATUS(0x0E)` of the Gyro
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroFifoStatus {
+    /// Frames Stored in the FIFO
+    #[bits(7)]
+    pub frames: u8,
+    /// FIFO Overrun, Cleared by Writing `FIFO_CONFIG_1`
+    pub overrun: bool,
+}
+
+///
+/// # Gyro FIFO Mode
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GyroFifoMode {
+    /// Stop Collecting when Full
+    Fifo = 0x01,
+    /// Discard the Oldest Frames when Full
+    #[fallback]
+    Stream = 0x02,
+}
+
+///
+/// # `FIFO_CONFIG_1(0x3E)` of the Gyro
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroFifoConfig1 {
+    /// Stored Axes, `0` for X, Y and Z
+    #[bits(2)]
+    pub data_select: u8,
+    #[bits(4)]
+    __: u8,
+    #[bits(2)]
+    pub mode: GyroFifoMode,
+}
+
+///
+/// # `FIFO_CONFIG_1(0x49)` of the Accel
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct AccFifoConfig1 {
+    #[bits(2)]
+    __: u8,
+    /// Store INT2 Input in the FIFO
+    pub int2_input_en: bool,
+    /// Store INT1 Input in the FIFO
+    pub int1_input_en: bool,
+    /// Must be `1`
+    #[bits(1, default = true)]
+    pub fixed: bool,
+    __: bool,
+    /// Store Accel Data in the FIFO
+    pub acc_en: bool,
+    __: bool,
+}
+
+///
+/// # Accel Self-Test Excitation: `ACC_SELF_TEST(0x6D)`
+///
+#[bitenum]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccSelfTest {
+    #[fallback]
+    Off = 0x00,
+    Positive = 0x0D,
+    Negative = 0x09,
+}
+
+///
+/// # `GYRO_SELF_TEST(0x3C)`
+///
+#[bitfield(u8, defmt = cfg(feature = "defmt"))]
+#[derive(PartialEq)]
+pub struct GyroSelfTest {
+    /// Write `1` to Start the Built-In Self-Test
+    pub trig_bist: bool,
+    /// Self-Test Finished
+    pub bist_rdy: bool,
+    /// Self-Test Failed
+    pub bist_fail: bool,
+    __: bool,
+    /// Rate Output Plausible
+    pub rate_ok: bool,
+    #[bits(3)]
+    __: u8,
+}
@@ -0,0 +1,49 @@
+//!
+//! # BMI088 Self-Test
+//!
+//! - Accel: the difference between positive and negative excitation
+//!   must exceed the datasheet limits, at ±24g and ODR=1600Hz.
+//! - Gyro: the built-in self-test must finish without failure.
+//!
+//! See the BMI088 datasheet chapter 4.6 (Sensor Self-Test).
+//!
+
+/// Minimum Accel Self-Test Response in mg: X, Y, Z
+pub const ACC_LIMITS: [f64; 3] = [1000., 1000., 500.];
+
+///
+/// # Self-Test Report
+///
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestReport {
+    /// Accel Response, Positive minus Negative, in mg
+    pub acc_delta: [f64; 3],
+    /// Accel Response above [`ACC_LIMITS`] per Axis
+    pub acc_pass: [bool; 3],
+    /// Gyro Built-In Self-Test Passed
+    pub gyro_pass: bool,
+    /// Gyro Rate Output Plausible
+    pub gyro_rate_ok: bool,
+}
+
+impl SelfTestReport {
+    pub(crate) fn new(pos: [f64; 3], neg: [f64; 3], gyro_pass: bool, gyro_rate_ok: bool) -> Self {
+        let acc_delta = core::array::from_fn(|i: _| pos[i] - neg[i]);
+        let acc_pass = core::array::from_fn(|i: _| acc_delta[i] >= ACC_LIMITS[i]);
+
+        Self {
+            acc_delta,
+            acc_pass,
+            gyro_pass,
+            gyro_rate_ok,
+        }
+    }
+
+    ///
+    /// # All Checks Passed
+    ///
+    pub fn passed(&self) -> bool {
+        self.acc_pass.iter().all(|&x| x) && self.gyro_pass && self.gyro_rate_ok
+    }
+}
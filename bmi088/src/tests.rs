@@ -0,0 +1,624 @@
+//!
+//! # Host Tests
+//!
+//! Run with `cargo test -p bmi088 --target host-tuple`.
+//!
+
+use crate::fifo::{self, AccFifo, GyroFifo};
+use crate::heat::{HeatConfig, HeatControl, HeatState};
+use crate::monitor::{Monitor, MonitorLimits};
+use crate::register::{AccRange, GyroBandwidth, GyroRange};
+use crate::selftest::ACC_LIMITS;
+use crate::{BMI088, Bmi088Config, Bmi088Error, DataReady, FifoConfig, FifoStats};
+use core::convert::Infallible;
+use embassy_futures::block_on;
+use embedded_hal::digital::ErrorType as PinErrorType;
+use embedded_hal::spi::{ErrorType, Operation};
+use embedded_hal_async::{delay::DelayNs, digital::Wait, spi::SpiDevice};
+
+const CONFIG: Bmi088Config = Bmi088Config::new();
+
+///
+/// # Mock SPI Device
+///
+/// Emulates a register file and logs every MOSI frame.
+/// Reads are answered after `dummy` bytes, burst reads of
+/// `fifo_reg` are answered from `fifo`. `hook` runs after writes.
+///
+struct Mock {
+    regs: [u8; 0x80],
+    dummy: usize,
+    stuck: Option<u8>,
+    hook: fn(&mut [u8; 0x80], u8, u8),
+    fifo_reg: u8,
+    fifo: Vec<u8>,
+    log: Vec<Vec<u8>>,
+}
+
+impl Mock {
+    fn acc() -> Self {
+        let mut regs = [0; _];
+        regs[0x00] = 0x1E;
+        Self {
+            regs,
+            dummy: 1,
+            stuck: None,
+            hook: |_, _, _| {},
+            fifo_reg: 0x26,
+            fifo: Vec::new(),
+            log: Vec::new(),
+        }
+    }
+
+    fn gyro() -> Self {
+        let mut regs = [0; _];
+        regs[0x00] = 0x0F;
+        Self {
+            regs,
+            dummy: 0,
+            stuck: None,
+            hook: |_, _, _| {},
+            fifo_reg: 0x3F,
+            fifo: Vec::new(),
+            log: Vec::new(),
+        }
+    }
+
+    fn frame(&mut self, buf: &mut [u8]) {
+        self.log.push(buf.to_vec());
+
+        let reg = (buf[0] & 0x7F) as usize;
+        if buf[0] & 0x80 == 0 {
+            if self.stuck != Some(reg as u8) {
+                self.regs[reg] = buf[1];
+            }
+            (self.hook)(&mut self.regs, reg as u8, buf[1]);
+            return;
+        }
+
+        let fifo = match reg == self.fifo_reg as usize {
+            true => self.fifo.len().min(buf.len() - 1 - self.dummy),
+            false => 0,
+        };
+        let mut fifo = self.fifo.drain(..fifo).chain(core::iter::repeat(0x80));
+
+        for (i, b) in buf[1..].iter_mut().enumerate() {
+            *b = match i.checked_sub(self.dummy) {
+                Some(_) if reg == self.fifo_reg as usize => fifo.next().unwrap(),
+                Some(n) => self.regs[reg + n],
+                None => 0xAA, // Dummy Byte
+            };
+        }
+    }
+}
+
+impl ErrorType for Mock {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Mock {
+    async fn transaction(&mut self, ops: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        for op in ops {
+            match op {
+                Operation::TransferInPlace(buf) => self.frame(buf),
+                Operation::Write(buf) => self.frame(&mut buf.to_vec()),
+                _ => unimplemented!(),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Interrupt Line: Fires Immediately, or Never
+struct Pin(bool);
+
+impl PinErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl Wait for Pin {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        self.wait_for_any_edge().await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        self.wait_for_any_edge().await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_for_any_edge().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        self.wait_for_any_edge().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        match self.0 {
+            true => Ok(()),
+            false => core::future::pending().await,
+        }
+    }
+}
+
+/// Delay: Returns Immediately
+struct Delay;
+
+impl DelayNs for Delay {
+    async fn delay_ns(&mut self, _: u32) {}
+}
+
+fn le(regs: &mut [u8], vals: [i16; 3]) {
+    for (i, v) in vals.iter().enumerate() {
+        regs[i * 2..i * 2 + 2].copy_from_slice(&v.to_le_bytes());
+    }
+}
+
+#[test]
+fn init_sequence() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.init()), Ok(()));
+
+    #[rustfmt::skip]
+    let expected: &[&[u8]] = &[
+        &[0x80, 0xFF, 0xFF], &[0x7E, 0xB6], &[0x80, 0xFF, 0xFF], &[0x80, 0xFF, 0xFF],
+        &[0x7D, 0x04], &[0xFD, 0xFF, 0xFF],
+        &[0x7C, 0x00], &[0xFC, 0xFF, 0xFF],
+        &[0x40, 0xA9], &[0xC0, 0xFF, 0xFF],
+        &[0x41, 0x02], &[0xC1, 0xFF, 0xFF],
+    ];
+    assert_eq!(acc.log, expected);
+
+    #[rustfmt::skip]
+    let expected: &[&[u8]] = &[
+        &[0x80, 0xFF], &[0x14, 0xB6], &[0x80, 0xFF], &[0x80, 0xFF],
+        &[0x0F, 0x00], &[0x8F, 0xFF],
+        &[0x10, 0x82], &[0x90, 0xFF],
+        &[0x11, 0x00], &[0x91, 0xFF],
+        &[0x15, 0x80], &[0x95, 0xFF],
+        &[0x16, 0x02], &[0x96, 0xFF],
+        &[0x18, 0x01], &[0x98, 0xFF],
+    ];
+    assert_eq!(gyro.log, expected);
+}
+
+#[test]
+fn init_wrong_chip_id() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    acc.regs[0x00] = 0x1F;
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.init()), Err(Bmi088Error::AccChipId(0x1F)));
+
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    gyro.regs[0x00] = 0x00;
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.init()), Err(Bmi088Error::GyroChipId(0x00)));
+}
+
+#[test]
+fn init_readback_mismatch() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    gyro.stuck = Some(0x10);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    let err = Bmi088Error::Readback {
+        reg: 0x10,
+        expected: 0x82,
+        actual: 0x00,
+    };
+    assert_eq!(block_on(imu.init()), Err(err));
+}
+
+#[test]
+fn read_gyro_scale() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    le(&mut gyro.regs[0x02..], [16384, -16384, 0]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // ±2000dps: 16.384 LSB/dps
+    assert_eq!(block_on(imu.read_gyro()), Ok((1000., -1000., 0.)));
+    assert_eq!(gyro.log, [[0x82, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]]);
+}
+
+#[test]
+fn read_acc_scale() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    le(&mut acc.regs[0x12..], [8192, -8192, 2731]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // ±12g: 32768 LSB / 12000mg, the Dummy Byte is Skipped
+    let (x, y, z) = block_on(imu.read_acc()).unwrap();
+    assert_eq!((x, y), (3000., -3000.));
+    assert!((z - 1000.).abs() < 0.5);
+    assert_eq!(acc.log, [[0x92, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]]);
+}
+
+#[test]
+fn read_temp_scale() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    acc.regs[0x22..0x24].copy_from_slice(&[0x16, 0x00]);
+    acc.regs[0x24..0x26].copy_from_slice(&[0xFC, 0x00]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // 0x0B0 * 0.125 + 23 = 45°C
+    assert_eq!(block_on(imu.read_temp()), Ok(45.));
+    assert_eq!(acc.log, [[0xA2, 0xFF, 0xFF, 0xFF]]);
+
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    acc.regs[0x22..0x24].copy_from_slice(&[0xFC, 0x00]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // 0x7E0 - 0x800 = -32 -> 19°C
+    assert_eq!(block_on(imu.read_temp()), Ok(19.));
+}
+
+#[test]
+fn wait_new_data_timeout() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.wait_new_data()), Ok(()));
+
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(false), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.wait_new_data()), Err(Bmi088Error::Timeout));
+}
+
+#[test]
+fn config_scale_factors() {
+    let mut config = Bmi088Config::new();
+    config.gyro_range = GyroRange::Dps500;
+    config.gyro_bandwidth = GyroBandwidth::Odr400Fbw47;
+    config.acc_range = AccRange::G3;
+
+    assert_eq!(config.gyro_scale(), 1. / 65.536);
+    assert_eq!(config.acc_scale(), 3000. / 32768.);
+    assert_eq!(config.gyro_period(), 0.0025);
+
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    le(&mut gyro.regs[0x02..], [6554, 0, -32768]);
+    le(&mut acc.regs[0x12..], [10923, 0, -32768]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, config);
+
+    let (x, _, z) = block_on(imu.read_gyro()).unwrap();
+    assert!((x - 100.).abs() < 0.01 && z == -500.);
+    let (x, _, z) = block_on(imu.read_acc()).unwrap();
+    assert!((x - 1000.).abs() < 0.1 && z == -3000.);
+}
+
+#[test]
+fn configure_at_runtime() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    let mut config = Bmi088Config::new();
+    config.gyro_range = GyroRange::Dps250;
+    config.gyro_bandwidth = GyroBandwidth::Odr2000Fbw532;
+    config.int_pin.active_high = true;
+    assert_eq!(block_on(imu.configure(config)), Ok(()));
+    assert_eq!(imu.config(), &config);
+
+    // GYRO_RANGE, GYRO_BANDWIDTH (Bit 7 Reads as 1), INT3_INT4_IO_CONF
+    assert_eq!(gyro.regs[0x0F], 0x03);
+    assert_eq!(gyro.regs[0x10], 0x80);
+    assert_eq!(gyro.regs[0x16], 0x03);
+}
+
+#[test]
+fn init_fifo_mode() {
+    let mut config = Bmi088Config::new();
+    config.fifo = Some(FifoConfig { watermark: 10 });
+
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, config);
+    assert_eq!(block_on(imu.init()), Ok(()));
+
+    // GYRO_INT_CTRL, INT3_INT4_IO_MAP: FIFO instead of Data Ready
+    assert_eq!((gyro.regs[0x15], gyro.regs[0x18]), (0x40, 0x04));
+    // FIFO_CONFIG_1, FIFO_CONFIG_0, FIFO_WM_ENABLE
+    assert_eq!((gyro.regs[0x3E], gyro.regs[0x3D]), (0x80, 10));
+    assert_eq!(gyro.regs[0x1E], 0x88);
+    // FIFO_DOWNS, FIFO_CONFIG_0, FIFO_CONFIG_1
+    assert_eq!(acc.regs[0x45..0x4A], [0x80, 0, 0, 0x02, 0x50]);
+}
+
+#[test]
+fn read_gyro_fifo() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 32]);
+    gyro.regs[0x0E] = 0x83; // Overrun, 3 Frames
+    for v in [[16384, 0, 0], [0, 16384, 0], [0, 0, 16384]] {
+        le(&mut gyro.regs[0x70..], v);
+        gyro.fifo.extend_from_slice(&gyro.regs[0x70..0x76]);
+    }
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // Only 2 Frames Fit, the Third Stays in the FIFO
+    let mut out = [[0.; 3]; 2];
+    let res = block_on(imu.read_gyro_fifo(&mut out));
+    let expected = GyroFifo {
+        frames: 2,
+        overrun: true,
+    };
+    assert_eq!(res, Ok(expected));
+    assert_eq!(out, [[1000., 0., 0.], [0., 1000., 0.]]);
+    assert_eq!(imu.fifo_stats().gyro_overruns, 1);
+
+    // Status, Burst Read of 2 Frames, Overrun Cleared by FIFO_CONFIG_1
+    assert_eq!(gyro.log[1].len(), 13);
+    assert_eq!(gyro.log[2], [0x3E, 0x80]);
+    assert_eq!(gyro.fifo.len(), 6);
+}
+
+#[test]
+fn read_acc_fifo() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 32]);
+    #[rustfmt::skip]
+    let data = [
+        0x84, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, // X=8192
+        0x40, 0x03,                               // Skip 3 Frames
+        0x48, 0x00,                               // Config Change
+        0x87, 0x00, 0x00, 0x00, 0xE0, 0x00, 0x00, // Y=-8192, Tagged
+        0x44, 0x56, 0x34, 0x12,                   // Sensortime
+    ];
+    acc.regs[0x24] = data.len() as u8;
+    acc.fifo = data.to_vec();
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    let mut out = [[0.; 3]; 1];
+    let res = block_on(imu.read_acc_fifo(&mut out));
+    let expected = AccFifo {
+        frames: 1,
+        skipped: 3,
+        dropped: 1,
+        sensortime: Some(0x123456),
+    };
+    assert_eq!(res, Ok(expected));
+    assert_eq!(out, [[3000., 0., 0.]]);
+
+    let stats = FifoStats {
+        gyro_overruns: 0,
+        acc_skipped: 3,
+        dropped: 1,
+    };
+    assert_eq!(imu.fifo_stats(), &stats);
+    assert_eq!(acc.log[1].len(), data.len() + 2);
+}
+
+#[test]
+fn parse_acc_fifo_stops() {
+    let mut out = [[0.; 3]; 4];
+
+    // Empty FIFO Reads as 0x80
+    let res = fifo::parse_acc_fifo(&[0x80, 0x00], 1., &mut out);
+    assert_eq!(res, AccFifo::default());
+
+    // Truncated Data Frame after a Drop Frame
+    let data = [0x50, 0x01, 0x84, 0x01, 0x00, 0x02];
+    let res = fifo::parse_acc_fifo(&data, 1., &mut out);
+    assert_eq!(res, AccFifo::default());
+
+    // Frames before an Unknown Header are Kept
+    let data = [0x84, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0xFF, 0x84];
+    let res = fifo::parse_acc_fifo(&data, 0.5, &mut out);
+    assert_eq!(res.frames, 1);
+    assert_eq!(out[0], [0.5, 1., 1.5]);
+}
+
+#[test]
+fn init_acc_drdy() {
+    let mut config = Bmi088Config::new();
+    config.acc_drdy = true;
+
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, config);
+    assert_eq!(block_on(imu.init()), Ok(()));
+
+    // INT1_IO_CONF: Output, Open-Drain, Active Low
+    assert_eq!(acc.regs[0x53], 0x0C);
+    // INT1_INT2_MAP_DATA: Data Ready to INT1
+    assert_eq!(acc.regs[0x58], 0x04);
+}
+
+#[test]
+fn wait_data_source() {
+    let mut config = Bmi088Config::new();
+    config.acc_drdy = true;
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+
+    // Accel Interrupt not Wired: Gyro Only
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, config);
+    assert_eq!(block_on(imu.wait_data()), Ok(DataReady::Gyro));
+
+    let imu = BMI088::new(&mut acc, &mut gyro, Pin(false), Delay, &mut buf, config);
+    let mut imu = imu.with_acc_int(Pin(true));
+    assert_eq!(block_on(imu.wait_data()), Ok(DataReady::Acc));
+
+    let imu = BMI088::new(&mut acc, &mut gyro, Pin(false), Delay, &mut buf, config);
+    let mut imu = imu.with_acc_int(Pin(false));
+    assert_eq!(block_on(imu.wait_data()), Err(Bmi088Error::Timeout));
+
+    // Wired but Disabled
+    let imu = BMI088::new(&mut acc, &mut gyro, Pin(false), Delay, &mut buf, CONFIG);
+    let mut imu = imu.with_acc_int(Pin(true));
+    assert_eq!(block_on(imu.wait_data()), Err(Bmi088Error::Timeout));
+}
+
+/// Accel Self-Test Response: ±(X, Y, Z) LSB at ±24g
+fn acc_excite(regs: &mut [u8; 0x80], reg: u8, val: u8) {
+    match (reg, val) {
+        (0x6D, 0x0D) => le(&mut regs[0x12..], [1000, 1000, 500]),
+        (0x6D, 0x09) => le(&mut regs[0x12..], [-1000, -1000, -200]),
+        _ => {}
+    }
+}
+
+#[test]
+fn self_test_pass() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    acc.hook = acc_excite;
+    gyro.regs[0x3C] = 0x12; // bist_rdy, rate_ok
+    gyro.stuck = Some(0x3C);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    let report = block_on(imu.self_test()).unwrap();
+    assert!(report.passed(), "{report:?}");
+    assert!((report.acc_delta[0] - 2000. * 24000. / 32768.).abs() < 1e-9);
+    assert!(report.acc_delta[2] > ACC_LIMITS[2]);
+
+    // Excitation Off, then Soft Reset and Configuration Restored
+    let off = acc
+        .log
+        .iter()
+        .position(|f: _| f[..] == [0x6D, 0x00])
+        .unwrap();
+    assert_eq!(acc.log[off + 2], [0x7E, 0xB6]);
+    assert_eq!((acc.regs[0x40], acc.regs[0x41]), (0xA9, 0x02));
+}
+
+#[test]
+fn self_test_fail() {
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    gyro.regs[0x3C] = 0x16; // bist_rdy, bist_fail, rate_ok
+    gyro.stuck = Some(0x3C);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+
+    // No Accel Response
+    let report = block_on(imu.self_test()).unwrap();
+    assert!(!report.passed());
+    assert_eq!(report.acc_pass, [false; 3]);
+    assert!(!report.gyro_pass && report.gyro_rate_ok);
+
+    // Gyro Self-Test never Finishes
+    let (mut acc, mut gyro, mut buf) = (Mock::acc(), Mock::gyro(), [0; 16]);
+    gyro.stuck = Some(0x3C);
+    let mut imu = BMI088::new(&mut acc, &mut gyro, Pin(true), Delay, &mut buf, CONFIG);
+    assert_eq!(block_on(imu.self_test()), Err(Bmi088Error::Timeout));
+}
+
+#[test]
+fn monitor_vibration_and_clipping() {
+    let limits = MonitorLimits {
+        window: 100,
+        ..MonitorLimits::DEFAULT
+    };
+    let mut m = Monitor::new(&CONFIG, limits);
+
+    // ±0.1g Square Wave on X around 1g on Z, a few Clipped Gyro Samples
+    let full = 32767. * CONFIG.gyro_scale();
+    let mut report = None;
+    for i in 0..100 {
+        let x = if i % 2 == 0 { 100. } else { -100. };
+        let gyro = if i < 3 {
+            [full, 0., 0.]
+        } else {
+            [i as f64, 0., 0.]
+        };
+        m.gyro(gyro);
+        report = report.or(m.acc([x, i as f64 * 1e-3, 1000.]));
+    }
+
+    let report = report.unwrap();
+    assert!((report.vibration[0] - 0.1).abs() < 1e-9);
+    assert!(report.vibration[2] < 1e-9);
+    assert!((report.norm - 1.005).abs() < 1e-3);
+    assert_eq!((report.gyro_clips, report.acc_clips), (3, 0));
+    assert!(!report.faults.any());
+
+    // Clipping most of a Window
+    for _ in 0..100 {
+        m.gyro([full, 0., 0.]);
+        m.acc([0., -24000., 1000.]);
+    }
+    let f = m.faults();
+    assert!(f.gyro_clipping && f.gyro_stuck);
+    assert!(f.acc_clipping && f.acc_stuck);
+}
+
+#[test]
+fn monitor_stuck_and_norm() {
+    let limits = MonitorLimits {
+        window: 10,
+        stuck: 20,
+        norm_windows: 3,
+        ..MonitorLimits::DEFAULT
+    };
+    let mut m = Monitor::new(&CONFIG, limits);
+
+    // Frozen Gyro, Changing Accel
+    for i in 0..20 {
+        m.gyro([1., 2., 3.]);
+        m.acc([0., i as f64, 1000.]);
+        assert!(!m.faults().gyro_stuck);
+    }
+    m.gyro([1., 2., 3.]);
+    assert!(m.faults().gyro_stuck);
+    m.gyro([1., 2., 3.5]);
+    assert!(!m.faults().gyro_stuck);
+
+    // Free Fall for Three Windows
+    for w in 0..3 {
+        assert!(!m.faults().acc_norm, "Window {}", w);
+        for i in 0..10 {
+            m.acc([i as f64, 0., 0.]);
+        }
+    }
+    assert!(m.faults().acc_norm);
+
+    // One Good Window Clears it
+    for i in 0..10 {
+        m.acc([0., i as f64, 1000.]);
+    }
+    assert!(!m.faults().any());
+}
+
+const HEAT: HeatConfig = HeatConfig {
+    setpoint: 45.,
+    cutoff: 60.,
+    period: 200,
+    dt: 0.1,
+    kp: 0.2,
+    ki: 0.02,
+    kd: 0.,
+    duty_max: 1.,
+    band: 0.5,
+    settle: 3,
+    timeout: 10,
+    stale: 10,
+};
+
+#[test]
+fn heat_settles_and_cuts_off() {
+    let mut h = HeatControl::new(HEAT);
+
+    // Cold: Full Duty, Warming Up
+    assert_eq!(h.update(20.), Some(1.));
+    assert_eq!(h.state(), HeatState::WarmingUp);
+
+    // At the Setpoint for `settle` Updates
+    for _ in 0..3 {
+        assert!(h.update(45.).is_some());
+    }
+    assert_eq!(h.state(), HeatState::Stable);
+
+    // Cutoff Latches, even Back in Range
+    assert_eq!(h.update(61.), None);
+    assert_eq!(h.update(45.), None);
+    assert_eq!(h.state(), HeatState::OverTemp);
+}
+
+#[test]
+fn heat_timeout_latches() {
+    let mut h = HeatControl::new(HEAT);
+
+    for _ in 0..10 {
+        assert_eq!(h.state(), HeatState::WarmingUp);
+        h.update(30.);
+    }
+    assert_eq!(h.state(), HeatState::TimedOut);
+
+    // Settling Late Never Clears it, still Regulating
+    for _ in 0..10 {
+        assert!(h.update(45.).is_some());
+    }
+    assert_eq!(h.state(), HeatState::TimedOut);
+}
@@ -4,9 +4,11 @@ use crate::system::*;
 pub async fn main() {
     let mut t = utils::init_ticker!(1);
 
-    SysMode::Normal.set();
+    let _ = SysMode::Disarmed.enter(Reason::Booted);
 
     loop {
+        Task::Controller.check_in();
+
         t.next().await
     }
 }
@@ -2,6 +2,7 @@
 #![no_main]
 
 use utils::prelude::*;
+use utils::profile;
 
 mod controller;
 mod system;
@@ -20,11 +21,16 @@ async fn entry(s: embassy_executor::Spawner) {
         split_resources!(p)
     };
 
-    s.must_spawn(tasks::health::task());
+    s.must_spawn(profile::named("Health", tasks::health::task(r.watchdog)));
 
-    s.must_spawn(tasks::blinky::task(r.blinky));
+    s.must_spawn(profile::named("Blinky", tasks::blinky::task(r.blinky)));
 
-    s.must_spawn(tasks::buzzer::task(r.buzzer));
+    // Minimal Safe Mode after a Boot Loop: Outputs Stay Off
+    if utils::panic::boot_loop() {
+        return system::Task::park_all();
+    }
 
-    s.must_spawn(controller::main());
+    s.must_spawn(profile::named("Buzzer", tasks::buzzer::task(r.buzzer)));
+
+    s.must_spawn(profile::named("Controller", controller::main()));
 }
@@ -19,6 +19,10 @@ assign_resources! {
         dma: BDMA_CH0,
     }
 
+    watchdog: WatchdogSrc {
+        iwdg_p: IWDG1,
+    }
+
     buzzer: BuzzerSrc {
         tim_p: TIM12,
         buzz_pin: PB15, // CH2
@@ -25,6 +25,8 @@ pub async fn task(p: BlinkySrc) -> ! {
     let mut hue = 0.;
 
     loop {
+        Task::Blinky.check_in();
+
         let (r, g, b) = color_wheel(hue as _);
         hue = (hue + SPEED) % 1536.;
 
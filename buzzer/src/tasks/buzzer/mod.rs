@@ -32,6 +32,7 @@ pub async fn task(p: BuzzerSrc) -> ! {
     );
 
     let mut buzzer: _ = Buzzer::new(beep_g, Channel::Ch2);
+    utils::panic::on_panic(silence);
 
     loop {
         // for &(f, d) in TUNE {
@@ -42,3 +43,12 @@ pub async fn task(p: BuzzerSrc) -> ! {
         buzzer.play(TUNE).await;
     }
 }
+
+///
+/// # Silence on Panic
+///
+/// Safe-state hook, drives TIM12 CH2 low behind the driver's back.
+///
+fn silence() {
+    crate::hal::pac::TIM12.ccr(1).write(|w: _| w.set_ccr(0));
+}
@@ -1,12 +1,62 @@
 use crate::system::*;
+use crate::tasks::bmi088;
+use utils::devices::Health;
 
 #[embassy_executor::task]
 pub async fn main() {
     let mut t = utils::init_ticker!(1);
 
-    SysMode::Normal.set();
+    let mut imu = match bmi088::receiver() {
+        None => panic!("{}: No IMU Receiver Left!!!", file!()),
+        Some(x) => x,
+    };
+
+    let mut events = match Device::subscribe() {
+        None => panic!("{}: No Event Subscriber Left!!!", file!()),
+        Some(x) => x,
+    };
+
+    // Ready once the IMU is Calibrated, not Watched until then
+    Task::Controller.park();
+    SysMode::Disarmed.wait_for().await;
+
+    let mut halted = false;
 
     loop {
+        Task::Controller.check_in();
+
+        if let Some(event) = events.try_next_message_pure() {
+            defmt::info!("Controller: {:?}", event);
+        }
+
+        // Outputs are Cut as soon as a Critical Device is Lost,
+        // or the System Leaves the Ready Modes, e.g. a Heater Fault
+        let halt = halted_by(SysMode::get(), Device::health());
+        if halt != halted {
+            match halt {
+                true => defmt::warn!("Controller: Outputs Halted in {:?}", SysMode::get()),
+                false => defmt::info!("Controller: Outputs Resumed"),
+            }
+            halted = halt;
+        }
+
+        // Latest Attitude, `None` until the IMU is Ready
+        let _attitude: Option<bmi088::ImuData> = match halted {
+            true => None,
+            false => imu.try_get(),
+        };
+
         t.next().await
     }
 }
+
+///
+/// # Outputs Halted
+///
+/// Outputs are live only while `Disarmed`, `Armed` or `Degraded`,
+/// which keeps running, with no critical device lost.
+///
+fn halted_by(mode: SysMode, health: Health) -> bool {
+    let ready = matches!(mode, SysMode::Disarmed | SysMode::Armed | SysMode::Degraded);
+    !ready || health == Health::Critical
+}
@@ -2,6 +2,7 @@
 #![no_main]
 
 use utils::prelude::*;
+use utils::profile;
 
 mod controller;
 mod system;
@@ -9,6 +10,8 @@ mod system;
 mod tasks {
     pub mod blinky;
     pub mod bmi088;
+    pub mod buzzer;
+    pub mod console;
     pub mod health;
 }
 
@@ -20,11 +23,23 @@ async fn entry(s: embassy_executor::Spawner) {
         split_resources!(p)
     };
 
-    s.must_spawn(tasks::health::task());
+    utils::storage::init(r.storage.flash_p);
 
-    s.must_spawn(tasks::blinky::task(r.blinky));
+    s.must_spawn(profile::named("Health", tasks::health::task(r.watchdog)));
 
-    s.must_spawn(tasks::bmi088::task(r.imu));
+    s.must_spawn(profile::named("Blinky", tasks::blinky::task(r.blinky)));
 
-    s.must_spawn(controller::main());
+    // Minimal Safe Mode after a Boot Loop: Outputs Stay Off
+    if utils::panic::boot_loop() {
+        tasks::blinky::indicate(Some((255, 0, 255)));
+        return system::Task::park_all();
+    }
+
+    s.must_spawn(profile::named("Buzzer", tasks::buzzer::task(r.buzzer)));
+
+    s.must_spawn(profile::named("BMI088", tasks::bmi088::task(r.imu, r.heat)));
+
+    s.must_spawn(profile::named("Controller", controller::main()));
+
+    s.must_spawn(profile::named("Console", tasks::console::task(r.uart1)));
 }
@@ -5,47 +5,45 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
-///
-/// # Device Enumeration
-///
-#[repr(usize)]
-#[derive(defmt::Format, Debug, PartialEq)]
-pub enum Device {
-    Placeholder = 0x0000,
+utils::devices! {
+    interval_ms: 100;
+
+    /// Bosch BMI088 IMU
+    Bmi088 => {
+        name: "BMI088",
+        expire_ms: 500,
+        criticality: Critical,
+    },
 }
 
-///
-/// # Watch List of Monitored Devices
-///
-pub const WATCH_LIST: &[Device] = &[
-    // Device::Placeholder,
-];
-
-/// Settings for Heartbeat Monitoring
-impl Device {
-    /// Health Check Interval in ms
-    pub(self) const HEALTH_MS: u8 = 100;
-    /// Device Expiration Time in ms
-    pub(self) const EXPIRE_MS: u16 = 500;
+// `utils::storage` Erases a 128K Sector with the Executor Blocked,
+// Allow Twice its Stall, plus 1s for the Task Deadlines. Not Measured
+// on the Board yet, Check the Max of the `Flash Rewrite` Probe.
+utils::watchdog! {
+    timeout_ms: 2 * utils::storage::ERASE_MS + 1000;
+
+    /// Controller Main Loop
+    Controller => { deadline_ms: 100 },
+    /// Blinky Task
+    Blinky => { deadline_ms: 100 },
+    /// BMI088 Task, Boot Sequence and Sampling
+    Bmi088 => { deadline_ms: 500 },
 }
 
-mod devices;
-mod heartbeat;
 mod interrupts;
 mod resources;
-mod status;
 
 pub use interrupts::Irqs;
 pub use resources::*;
-pub use status::SysMode;
+pub use utils::status::{Reason, SysMode};
 
 /// # Private Imports
 mod private {
     pub use assign_resources::assign_resources;
     pub use utils::{atomic, bitenum, prelude::*};
 
-    pub use super::heartbeat::HeartBeat;
     pub use super::{Device, WATCH_LIST};
+    pub use utils::devices::HeartBeat;
 
     pub use hal::bind_interrupts;
     pub use hal::{Peri, peripherals};
@@ -19,6 +19,10 @@ assign_resources! {
         dma: BDMA_CH0,
     }
 
+    watchdog: WatchdogSrc {
+        iwdg_p: IWDG1,
+    }
+
     buzzer: BuzzerSrc {
         tim_p: TIM12,
         buzz_pin: PB15,
@@ -59,6 +63,11 @@ assign_resources! {
         // dma: MDMA_CH0
     }
 
+    /// for `utils::storage`, Internal Flash.
+    storage: StorageSrc {
+        flash_p: FLASH,
+    }
+
     pwm: PwmSrc {
         tim1_p: TIM1,
         pwm_1: PE13, // CH3
@@ -91,9 +100,6 @@ assign_resources! {
         dma_rx: DMA1_CH0,
         dma_tx: DMA2_CH0,
 
-        heat_p: TIM3,
-        heat_pin: PB1, // CH4
-
         acc_int: PE10,
         acc_exti: EXTI10,
         acc_cs: PC0,
@@ -103,6 +109,11 @@ assign_resources! {
         gyro_cs: PC3,
     }
 
+    heat: HeatSrc {
+        heat_p: TIM3,
+        heat_pin: PB1, // CH4
+    }
+
     uart1: Uart1Src {
         usart_p: USART1,
         usart_rx: PA10,
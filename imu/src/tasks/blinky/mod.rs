@@ -1,14 +1,41 @@
 //!
 //! # Blinky Task
 //!
+//! Cycles the color wheel, unless a fixed color is requested.
+//! Turns red as soon as a critical device goes offline.
+//!
+//! ## Show a Fixed Color
+//! ```rust
+//! blinky::indicate(Some((255, 0, 0)));
+//! blinky::indicate(None); // Back to the Color Wheel
+//! ```
+//!
 
 use crate::{hal, system::*};
 use hal::gpio::{Pull, Speed};
 use hal::spi::{BitOrder, Config, MODE_0, Spi};
 use hal::time::mhz;
+use utils::atomic::{AtomicU32, Ordering::Relaxed};
+use utils::devices::Health;
 
 const SPEED: f32 = 0.3;
 
+/// Indicated Color as `0x01RRGGBB`, `0` for the Color Wheel
+static INDICATE: AtomicU32 = AtomicU32::new(0);
+
+///
+/// # Indicate a Color
+///
+/// Overrides the color wheel until called with `None`.
+///
+pub fn indicate(color: Option<(u8, u8, u8)>) {
+    let bits = match color {
+        Some((r, g, b)) => u32::from_be_bytes([1, r, g, b]),
+        None => 0,
+    };
+    INDICATE.store(bits, Relaxed);
+}
+
 #[embassy_executor::task]
 pub async fn task(p: BlinkySrc) -> ! {
     let mut t = utils::init_ticker!(1);
@@ -22,10 +49,26 @@ pub async fn task(p: BlinkySrc) -> ! {
 
     let mut led = Spi::new_txonly_nosck(p.spi_p, p.led_pin, p.dma, config);
 
+    let mut events = match Device::subscribe() {
+        None => panic!("{}: No Event Subscriber Left!!!", file!()),
+        Some(x) => x,
+    };
+
     let mut hue = 0.;
+    let mut alarm = false;
 
     loop {
-        let (r, g, b) = color_wheel(hue as _);
+        Task::Blinky.check_in();
+
+        if events.try_next_message_pure().is_some() {
+            alarm = Device::health() == Health::Critical;
+        }
+
+        let (r, g, b) = match INDICATE.load(Relaxed).to_be_bytes() {
+            [0, ..] if alarm => (255, 0, 0),
+            [0, ..] => color_wheel(hue as _),
+            [_, r, g, b] => (r, g, b),
+        };
         hue = (hue + SPEED) % 1536.;
 
         let buf = ws2812_calc(r, g, b);
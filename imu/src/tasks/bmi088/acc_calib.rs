@@ -0,0 +1,353 @@
+//!
+//! # Six-Position Accel Calibration
+//!
+//! The board is placed with each axis pointing up, then down. At rest
+//! the sensor reads `raw = A·g + b`, so with the six ideal poses
+//! `g = ±eᵢ` the least squares solution is closed form:
+//!
+//! - `A` column `i`: `(raw₊ᵢ - raw₋ᵢ) / 2`
+//! - `b`: mean of `(raw₊ᵢ + raw₋ᵢ) / 2`
+//!
+//! Without cross-axis terms only the diagonal of `A` (the scale) is
+//! kept. The off-diagonal terms also absorb how far the faces were
+//! from level, so only enable them with a square fixture.
+//!
+//! The correction `acc = A⁻¹·(raw - b)` is stored in
+//! [`utils::storage`] and loaded on every boot.
+//!
+//! ## Request a Calibration
+//! ```rust
+//! bmi088::calibrate_acc(false); // Offset and Scale
+//! bmi088::calibrate_acc(true);  // Plus Cross-Axis Terms
+//! ```
+//!
+
+use crate::sync;
+use nalgebra::{Matrix3, Vector3};
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, signal::Signal};
+use utils::storage::{self, StorageError};
+
+/// Storage Slot of the Calibration
+const SLOT: usize = 0;
+/// Record Layout Version
+const VERSION: u8 = 1;
+/// Record: Version + Offset(3) + Matrix(9) as f64
+const RECORD: usize = 1 + 12 * 8;
+
+/// Window Length in s
+const WINDOW: f64 = 0.2;
+/// Consecutive Still Windows to Capture a Pose (1s)
+const HOLD: u32 = 5;
+/// Windows to Wait for a Pose before Giving Up (60s)
+const POSE_TIMEOUT: u32 = 300;
+
+/// Maximum Accel Variance per Axis: (5mg)² in g²
+const ACC_VAR_MAX: f64 = 2.5e-5;
+/// Maximum Gyro Rate while Still, Bias Included, in rad/s
+const GYRO_MAX: f64 = 0.1;
+/// Minimum Cosine to the Expected Gravity Direction (20°)
+const ALIGN_MIN: f64 = 0.94;
+
+/// Accepted Scale Range
+const SCALE_TOL: f64 = 0.1;
+/// Maximum Offset Magnitude in g
+const OFFSET_MAX: f64 = 0.2;
+/// Maximum Cross-Axis Term
+const CROSS_MAX: f64 = 0.1;
+
+static REQUEST: Signal<RM, bool> = Signal::new();
+
+///
+/// # Request a Calibration
+///
+/// Runs the guided routine before the next sample,
+/// with cross-axis terms if `cross_axis` is set.
+///
+pub fn request(cross_axis: bool) {
+    REQUEST.signal(cross_axis);
+}
+
+///
+/// # Take a Pending Request
+///
+pub(super) fn requested() -> Option<bool> {
+    REQUEST.try_take()
+}
+
+///
+/// # Calibration Pose
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub enum Face {
+    ZUp,
+    ZDown,
+    XUp,
+    XDown,
+    YUp,
+    YDown,
+}
+
+impl Face {
+    /// Order the Operator is Guided Through
+    pub const ALL: [Face; 6] = [
+        Self::ZUp,
+        Self::ZDown,
+        Self::XUp,
+        Self::XDown,
+        Self::YUp,
+        Self::YDown,
+    ];
+
+    ///
+    /// # Expected Reading at Rest in g
+    ///
+    pub fn gravity(self) -> Vector3<f64> {
+        match self {
+            Self::ZUp => Vector3::z(),
+            Self::ZDown => -Vector3::z(),
+            Self::XUp => Vector3::x(),
+            Self::XDown => -Vector3::x(),
+            Self::YUp => Vector3::y(),
+            Self::YDown => -Vector3::y(),
+        }
+    }
+
+    ///
+    /// # LED Color of the Pose
+    ///
+    /// Red, Green and Blue for X, Y and Z, dimmed when Down.
+    ///
+    pub const fn color(self) -> (u8, u8, u8) {
+        match self {
+            Self::XUp => (255, 0, 0),
+            Self::XDown => (40, 0, 0),
+            Self::YUp => (0, 255, 0),
+            Self::YDown => (0, 40, 0),
+            Self::ZUp => (0, 0, 255),
+            Self::ZDown => (0, 0, 40),
+        }
+    }
+}
+
+///
+/// # Accel Correction
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub struct AccCalib {
+    /// Offset in g
+    pub offset: Vector3<f64>,
+    /// Scale and Cross-Axis Correction
+    pub matrix: Matrix3<f64>,
+}
+
+impl AccCalib {
+    /// No Correction
+    pub const IDENTITY: Self = Self {
+        offset: Vector3::new(0., 0., 0.),
+        matrix: Matrix3::new(1., 0., 0., 0., 1., 0., 0., 0., 1.),
+    };
+
+    ///
+    /// # Correct a Raw Sample
+    ///
+    pub fn apply(&self, raw: &Vector3<f64>) -> Vector3<f64> {
+        self.matrix * (raw - self.offset)
+    }
+
+    ///
+    /// # Solve from the Six Poses
+    ///
+    /// `means` are the raw readings in the order of [`Face::ALL`].
+    /// Returns `None` if the result is implausible.
+    ///
+    pub fn solve(means: &[Vector3<f64>; 6], cross_axis: bool) -> Option<Self> {
+        let mut a = Matrix3::zeros();
+        let mut b = Vector3::zeros();
+
+        for (i, face) in Face::ALL.iter().enumerate() {
+            let axis = face.gravity().iamax();
+            let sign = face.gravity()[axis];
+            a.column_mut(axis).axpy(sign / 2., &means[i], 1.);
+            b += means[i] / 6.;
+        }
+
+        if !cross_axis {
+            a = Matrix3::from_diagonal(&a.diagonal());
+        }
+
+        let scale_ok = a.diagonal().iter().all(|s: _| (s - 1.).abs() < SCALE_TOL);
+        let cross_ok = (0..3)
+            .flat_map(|r: _| (0..3).map(move |c: _| (r, c)))
+            .all(|(r, c): _| r == c || a[(r, c)].abs() < CROSS_MAX);
+
+        if !scale_ok || !cross_ok || b.norm() > OFFSET_MAX {
+            return None;
+        }
+
+        Some(Self {
+            offset: b,
+            matrix: a.try_inverse()?,
+        })
+    }
+
+    ///
+    /// # Residual
+    ///
+    /// Worst deviation of the corrected norms from 1g, in g.
+    ///
+    pub fn residual(&self, means: &[Vector3<f64>; 6]) -> f64 {
+        means
+            .iter()
+            .map(|m: _| (self.apply(m).norm() - 1.).abs())
+            .fold(0., f64::max)
+    }
+
+    ///
+    /// # Load from Storage
+    ///
+    pub async fn load() -> Option<Self> {
+        let mut buf = [0; RECORD];
+        let len = storage::load(SLOT, &mut buf).await?;
+        Self::from_bytes(&buf[..len])
+    }
+
+    ///
+    /// # Save to Storage
+    ///
+    pub async fn save(&self) -> Result<(), StorageError> {
+        storage::store(SLOT, &self.to_bytes()).await
+    }
+
+    fn to_bytes(self) -> [u8; RECORD] {
+        let mut buf = [0; RECORD];
+        buf[0] = VERSION;
+
+        let values = self.offset.iter().chain(self.matrix.iter());
+        for (chunk, v) in buf[1..].as_chunks_mut::<8>().0.iter_mut().zip(values) {
+            chunk.copy_from_slice(&v.to_le_bytes());
+        }
+
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let [VERSION, data @ ..] = buf else {
+            return None;
+        };
+
+        if data.len() != RECORD - 1 {
+            return None;
+        }
+
+        let (values, _) = data.as_chunks::<8>();
+        let mut values = values.iter().map(|v: _| f64::from_le_bytes(*v));
+
+        Some(Self {
+            offset: Vector3::from_iterator(values.by_ref().take(3)),
+            matrix: Matrix3::from_iterator(values),
+        })
+    }
+}
+
+///
+/// # Pose Detector
+///
+/// Accepts a pose once the board has rested facing the expected
+/// direction for [`HOLD`] consecutive windows.
+///
+pub struct Pose {
+    face: Face,
+    len: u32,
+    n: u32,
+    mean: Vector3<f64>,
+    m2: Vector3<f64>,
+    moving: bool,
+
+    held: u32,
+    sum: Vector3<f64>,
+    windows: u32,
+}
+
+///
+/// # Pose Progress
+///
+#[derive(defmt::Format)]
+pub enum PoseState {
+    /// Waiting for the Board to Rest on the Face
+    Waiting,
+    /// Resting, Counting Still Windows
+    Holding { held: u32 },
+    /// Pose Captured, Mean Raw Reading in g
+    Captured(Vector3<f64>),
+    /// Pose not Reached in Time
+    Timeout,
+}
+
+impl Pose {
+    ///
+    /// # New Detector
+    ///
+    /// `odr` is the accel output data rate in Hz.
+    ///
+    pub fn new(face: Face, odr: f64) -> Self {
+        Self {
+            face,
+            len: ((odr * WINDOW) as u32).max(2),
+            n: 0,
+            mean: Vector3::zeros(),
+            m2: Vector3::zeros(),
+            moving: false,
+            held: 0,
+            sum: Vector3::zeros(),
+            windows: 0,
+        }
+    }
+
+    ///
+    /// # Push Fresh Accel Sample
+    ///
+    /// Raw accel in g, with the latest raw gyro in rad/s.
+    ///
+    pub fn push(&mut self, gyro: &Vector3<f64>, acc: &Vector3<f64>) -> PoseState {
+        if gyro.norm() > GYRO_MAX {
+            self.moving = true;
+        }
+
+        self.n += 1;
+        let delta = acc - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta.component_mul(&(acc - self.mean));
+
+        if self.n < self.len {
+            return self.state();
+        }
+
+        let var = self.m2 / (self.n - 1) as f64;
+        let still = !self.moving && var.iter().all(|&v| v < ACC_VAR_MAX);
+        let facing = self.mean.normalize().dot(&self.face.gravity()) > ALIGN_MIN;
+
+        match still && facing {
+            true => {
+                self.held += 1;
+                self.sum += self.mean;
+            }
+            false => (self.held, self.sum) = (0, Vector3::zeros()),
+        }
+
+        self.windows += 1;
+        (self.n, self.mean, self.m2, self.moving) = (0, Vector3::zeros(), Vector3::zeros(), false);
+
+        match (self.held >= HOLD, self.windows >= POSE_TIMEOUT) {
+            (true, _) => PoseState::Captured(self.sum / self.held as f64),
+            (false, true) => PoseState::Timeout,
+            (false, false) => self.state(),
+        }
+    }
+
+    fn state(&self) -> PoseState {
+        match self.held {
+            0 => PoseState::Waiting,
+            held => PoseState::Holding { held },
+        }
+    }
+}
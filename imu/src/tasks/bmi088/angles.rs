@@ -0,0 +1,112 @@
+//!
+//! # Output Angles
+//!
+//! Continuous yaw, Euler angles in the selected rotation order,
+//! world frame rates and linear acceleration, all derived from the
+//! filter attitude before publication.
+//!
+//! ## Zero the Yaw
+//! ```rust
+//! bmi088::zero_yaw();
+//! ```
+//!
+//! ## Select the Rotation Order
+//! ```rust
+//! bmi088::set_order(RotationOrder::Zxy);
+//! ```
+//!
+
+use super::output::ImuData;
+use crate::{sync, time::Instant};
+use attitude::frames::{euler, linear_acc, world_rate};
+use attitude::{Heading, RotationOrder};
+use nalgebra::{UnitQuaternion, Vector3};
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, signal::Signal};
+
+/// Standard Gravity in m/s²
+const GRAVITY: f64 = 9.80665;
+
+static ZERO: Signal<RM, ()> = Signal::new();
+static ORDER: Signal<RM, RotationOrder> = Signal::new();
+
+///
+/// # Zero Yaw
+///
+/// The current heading becomes zero, the turn count restarts.
+///
+pub fn zero_yaw() {
+    ZERO.signal(());
+}
+
+///
+/// # Select Rotation Order
+///
+/// Order of the published Euler angles.
+///
+pub fn set_order(order: RotationOrder) {
+    ORDER.signal(order);
+}
+
+///
+/// # Sample in the Body Frame
+///
+pub struct BodySample {
+    pub time: Instant,
+    pub acc_time: Instant,
+    /// Bias Corrected Rates in rad/s
+    pub gyro: Vector3<f64>,
+    /// Specific Force in g
+    pub acc: Vector3<f64>,
+    /// Sensor Temperature in °C
+    pub temp: f32,
+}
+
+pub struct Angles {
+    heading: Heading,
+    order: RotationOrder,
+}
+
+impl Angles {
+    pub fn new(order: RotationOrder) -> Self {
+        Self {
+            heading: Heading::new(),
+            order,
+        }
+    }
+
+    ///
+    /// # Derive Outputs
+    ///
+    /// Applies pending requests, then derives every output of `quat`.
+    ///
+    pub fn derive(&mut self, quat: &UnitQuaternion<f64>, s: &BodySample) -> ImuData {
+        if let Some(order) = ORDER.try_take() {
+            self.order = order;
+            defmt::info!("BMI088 Rotation Order: {:?}", order);
+        }
+
+        if ZERO.try_take().is_some() {
+            self.heading.zero(quat);
+            defmt::info!("BMI088 Yaw Zeroed");
+        }
+
+        let quat = self.heading.update(quat);
+        let (linear, linear_world) = linear_acc(&quat, &s.acc);
+
+        ImuData {
+            time: s.time,
+            acc_time: s.acc_time,
+            quat,
+            euler: euler(&quat, self.order),
+            order: self.order,
+            yaw: self.heading.yaw(),
+            turns: self.heading.turns(),
+            gyro: s.gyro,
+            gyro_world: world_rate(&quat, &s.gyro),
+            acc: s.acc * GRAVITY,
+            linear_acc: linear * GRAVITY,
+            linear_acc_world: linear_world * GRAVITY,
+            temp: s.temp,
+        }
+    }
+}
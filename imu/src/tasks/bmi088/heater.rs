@@ -0,0 +1,168 @@
+//!
+//! # BMI088 Heater
+//!
+//! Constant temperature control on a PWM driven heater resistor,
+//! see [`bmi088::heat`] for the controller.
+//!
+
+use crate::hal::timer;
+use crate::system::*;
+use crate::time::{Duration, Instant};
+use bmi088::heat::{HeatConfig, HeatControl, HeatState};
+
+use simple_pwm::SimplePwm as PWM;
+use timer::GeneralInstance4Channel as TIM;
+use timer::{Channel, simple_pwm};
+
+pub struct Heater<'t, P: TIM> {
+    pwm: PWM<'t, P>,
+    channel: Channel,
+    control: HeatControl,
+
+    tick: u32,
+    temp: f32,
+    fresh: Instant,
+    on: bool,
+}
+
+impl<'t, P: TIM> Heater<'t, P> {
+    pub fn new(pwm: PWM<'t, P>, ch: Channel, config: HeatConfig) -> Heater<'t, P> {
+        let mut this = Self {
+            pwm,
+            channel: ch,
+            control: HeatControl::new(config),
+            tick: 0,
+            temp: 0.,
+            fresh: Instant::now(),
+            on: true,
+        };
+
+        let mut heater: _ = this.pwm.channel(ch);
+        heater.set_duty_cycle_fully_off();
+        heater.enable();
+
+        this
+    }
+}
+
+impl<P: TIM> Heater<'_, P> {
+    ///
+    /// # Get Heater State
+    ///
+    #[inline]
+    pub fn state(&self) -> HeatState {
+        self.control.state()
+    }
+
+    ///
+    /// # Get Temperature
+    ///
+    /// Returns the last measured temperature in °C.
+    ///
+    #[inline]
+    pub fn temp(&self) -> f32 {
+        self.temp
+    }
+
+    ///
+    /// # Get Setpoint
+    ///
+    #[inline]
+    pub fn setpoint(&self) -> f32 {
+        self.control.config().setpoint
+    }
+
+    ///
+    /// # Control Step Due
+    ///
+    /// Call once per gyro sample, returns `true` every control period.
+    ///
+    pub fn due(&mut self) -> bool {
+        self.tick += 1;
+        if self.tick < self.control.config().period {
+            return false;
+        }
+
+        self.tick = 0;
+        true
+    }
+
+    ///
+    /// # Watch Temperature
+    ///
+    /// Call on every sample, failed or not. Cuts the heater when no
+    /// temperature arrived for `stale` control periods, the next
+    /// update turns it back on.
+    ///
+    pub fn watch(&mut self) {
+        let c = self.control.config();
+        let stale = Duration::from_micros((c.dt * 1e6) as u64) * c.stale;
+        if self.on && self.fresh.elapsed() > stale {
+            defmt::warn!(
+                "BMI088 Heater: No Temperature for {}ms, Off!",
+                stale.as_millis()
+            );
+            self.off();
+        }
+    }
+
+    ///
+    /// # Update Heater
+    ///
+    /// Run one control step with the measured temperature in °C.
+    ///
+    pub fn update(&mut self, temp: f32) -> HeatState {
+        self.temp = temp;
+        self.fresh = Instant::now();
+
+        let last = self.control.state();
+        let Some(duty) = self.control.update(temp) else {
+            if last != HeatState::OverTemp {
+                self.off();
+                defmt::error!("BMI088 Heater Over-Temperature: {}°C, Cutoff!!!", temp);
+                let _ = SysMode::Fault.enter(Reason::Hardware);
+            }
+            return HeatState::OverTemp;
+        };
+        self.set(duty);
+
+        match (last, self.control.state()) {
+            (HeatState::WarmingUp, HeatState::Stable) => {
+                defmt::info!("BMI088 Heater Stable at {}°C", temp);
+            }
+            (HeatState::WarmingUp, HeatState::TimedOut) => {
+                defmt::error!("BMI088 Heater Warm-Up Timed Out at {}°C!!!", temp);
+            }
+            _ => {}
+        }
+
+        defmt::trace!("BMI088 Heater: Temp={}°C, Duty={}", temp, duty);
+
+        self.control.state()
+    }
+}
+
+impl<P: TIM> Heater<'_, P> {
+    ///
+    /// # Heater Off
+    ///
+    /// Fail-safe cut, the next update turns it back on
+    /// unless latched by an over-temperature.
+    ///
+    pub fn off(&mut self) {
+        let mut heater: _ = self.pwm.channel(self.channel);
+        heater.set_duty_cycle_fully_off();
+        heater.disable();
+        self.on = false;
+    }
+
+    fn set(&mut self, duty: f32) {
+        let mut heater: _ = self.pwm.channel(self.channel);
+        let max = heater.max_duty_cycle() as f32;
+        heater.set_duty_cycle((duty * max) as u16);
+        if !self.on {
+            heater.enable();
+            self.on = true;
+        }
+    }
+}
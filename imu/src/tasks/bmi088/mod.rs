@@ -2,87 +2,533 @@
 //! # Imu(BMI088) Task
 //!
 
-use crate::system::*;
-use ahrs::{Ahrs, Mahony};
+use crate::tasks::{blinky, buzzer, buzzer::Cue};
+use crate::{hal, system::*};
+use attitude::bias::{GyroBias, Progress};
+use attitude::{Estimator, Filter, RotationOrder};
+use bmi088::heat::{HeatConfig, HeatState};
+use bmi088::{Bmi088Config, FifoConfig, Monitor, MonitorLimits, fifo::FIFO_BUFFER};
+use hal::{gpio::OutputType::PushPull, peripherals::TIM3, time::khz, timer};
 use libm::{atan2, sqrt};
 use nalgebra::{UnitQuaternion, Vector3};
 use utils::StaticCell;
 
+use low_level::CountingMode::EdgeAlignedUp;
+use timer::simple_pwm::{PwmPin, SimplePwm};
+use timer::{Channel, low_level};
+
+mod acc_calib;
+mod angles;
+mod heater;
+mod mounting;
+mod output;
+mod sampler;
+mod timing;
+mod tuning;
 mod typedef;
 
+pub use acc_calib::request as calibrate_acc;
+pub use angles::{set_order, zero_yaw};
+pub use mounting::{Orientation, Rotation, clear_trim, level_trim, set_mounting};
+pub use output::{ImuData, latest, receiver};
+pub use sampler::sensor_health;
+pub use timing::timing;
+pub use tuning::{select, tune};
+
+use acc_calib::{AccCalib, Face, Pose, PoseState};
+use angles::{Angles, BodySample};
+use heater::Heater;
+use mounting::{Mounting, Request};
+use sampler::{Sample, Sampler};
+use timing::Timing;
 use typedef::BMI088;
 
+/// Maximum Init and Self-Test Attempts before the Device is Offline
+const INIT_RETRY: u32 = 5;
+
+/// Mounting at Boot, until Changed at Runtime, see [`mounting`]
+const MOUNTING: Orientation = Orientation::Preset(Rotation::None);
+
+/// Rotation Order of the Euler Angles at Boot
+const EULER_ORDER: RotationOrder = RotationOrder::Zyx;
+
+/// BMI088 Range, Data Rate and Interrupt Configuration,
+/// FIFO Read every 10 Gyro Samples, Accel INT1 when not in FIFO Mode
+const IMU_CONFIG: Bmi088Config = Bmi088Config {
+    acc_drdy: true,
+    fifo: Some(FifoConfig { watermark: 10 }),
+    ..Bmi088Config::new()
+};
+
+/// Constant Temperature Control: 45°C, at 10Hz, Settled within 2min
+const HEAT_CONFIG: HeatConfig = HeatConfig {
+    setpoint: 45.,
+    cutoff: 60.,
+    period: (IMU_CONFIG.gyro_odr() / 10.) as u32,
+    dt: 0.1,
+
+    kp: 0.2,
+    ki: 0.02,
+    kd: 0.,
+    duty_max: 1.,
+
+    band: 0.5,
+    settle: 30,    // 3s
+    timeout: 1200, // 120s
+    stale: 10,     // 1s
+};
+
 #[unsafe(link_section = ".axisram.imu")]
-static BUFFER: StaticCell<[u8; 16]> = StaticCell::new();
+static BUFFER: StaticCell<[u8; FIFO_BUFFER]> = StaticCell::new();
+static SAMPLER: StaticCell<Sampler> = StaticCell::new();
 
 #[embassy_executor::task]
-pub async fn task(p: ImuSrc) -> ! {
+pub async fn task(p: ImuSrc, h: HeatSrc) -> ! {
     let buffer = BUFFER.init([0; _]);
-    let mut imu = BMI088::new(p, buffer);
+    let mut imu = typedef::new(p, buffer, IMU_CONFIG);
+
+    let heat_pin = PwmPin::new(h.heat_pin, PushPull);
+    let heat_g = SimplePwm::new(
+        h.heat_p,
+        None,
+        None,
+        None,
+        Some(heat_pin),
+        khz(20),
+        EdgeAlignedUp,
+    );
 
-    while imu.init().await == false {
-        defmt::warn!("BMI088 Init Failed, Retrying...");
+    let mut heater: _ = Heater::new(heat_g, Channel::Ch4, HEAT_CONFIG);
+    utils::panic::on_panic(heat_off);
+
+    // Power-On Check: Init and Self-Test before the IMU may be Used
+    let _ = SysMode::SelfTest.enter(Reason::SelfTest);
+    for retry in 1.. {
+        Task::Bmi088.check_in();
+
+        let res = async {
+            imu.init().await?;
+            imu.self_test().await
+        };
+
+        match res.await {
+            Ok(report) if report.passed() => {
+                defmt::info!("BMI088 Self-Test Passed: {:?}", report);
+                break;
+            }
+            Ok(report) => {
+                defmt::warn!(
+                    "BMI088 Self-Test Failed ({}/{}): {:?}",
+                    retry,
+                    INIT_RETRY,
+                    report
+                );
+            }
+            Err(e) => {
+                defmt::warn!("BMI088 Init Failed ({}/{}): {:?}", retry, INIT_RETRY, e);
+            }
+        }
+
+        if retry >= INIT_RETRY {
+            defmt::error!("BMI088 Power-On Check Failed, Device Offline!!!");
+            Device::Bmi088.kill();
+            let _ = SysMode::Fault.enter(Reason::SelfTestFailed);
+            Task::Bmi088.park();
+
+            loop {
+                core::future::pending::<()>().await
+            }
+        }
     }
 
-    let mut acc_last = imu.read_acc().await;
+    let sampler = SAMPLER.init(Sampler::new());
 
-    let quat: _ = UnitQuaternion::from_euler_angles(
-        atan2(acc_last.1, acc_last.2),
-        atan2(
-            -acc_last.0,
-            sqrt(acc_last.1 * acc_last.1 + acc_last.2 * acc_last.2),
-        ),
-        0.,
-    );
+    // 1s Monitor Windows
+    let limits = MonitorLimits {
+        window: acc_rate(&imu) as u32,
+        ..MonitorLimits::DEFAULT
+    };
+    sampler.set_monitor(Monitor::new(imu.config(), limits));
+
+    match AccCalib::load().await {
+        Some(calib) => {
+            defmt::info!("BMI088 Accel Calibration Loaded: {:?}", calib);
+            sampler.set_acc_calib(calib);
+        }
+        None => defmt::warn!("BMI088 Accel not Calibrated, Using Raw Readings!"),
+    }
 
-    // TODO: `0.001s` is determined by Gyro New Data Interrupt ODR(1000Hz)
-    let mut ahrs = Mahony::new_with_quat(0.001, 3.5, 0., quat);
+    let mut mount = Mounting::load().await.unwrap_or(Mounting::new(MOUNTING));
+    defmt::info!("BMI088 Mounting: {:?}", mount);
+
+    warm_up(&mut imu, &mut heater, sampler).await;
+
+    let mut bias = GyroBias::new(imu.config().gyro_odr());
+    calibrate(&mut imu, &mut heater, &mut bias, sampler).await;
+
+    let quat = level(&mut imu, &mut heater, sampler, &mount).await;
+    let _ = SysMode::Disarmed.enter(Reason::Calibrated);
+
+    let mut filter = Filter::new(quat, tuning::BOOT_FILTER.gains());
+    defmt::info!("BMI088 Estimator: {:?}", filter.kind());
+
+    let mut angles = Angles::new(EULER_ORDER);
+    let fifo = imu.config().fifo.is_some();
+    let mut timing = Timing::new(imu.config().gyro_period(), 1. / acc_rate(&imu), fifo);
 
     loop {
-        imu.wait_new_data().await;
+        tuning::apply(&mut filter);
 
-        // TODO: impl `Constant Temperature Control`: 45°C
-        let temp = imu.read_temp().await;
-        defmt::trace!("BMI088 Temp Data Ready: {:?}", temp);
+        // Calibration and Remounting are Refused while Armed
+        if let Some(cross_axis) = acc_calib::requested() {
+            match SysMode::Calibrating.enter(Reason::Calibration) {
+                Ok(()) => {
+                    six_position(&mut imu, &mut heater, sampler, cross_axis).await;
+                    filter.reset(level(&mut imu, &mut heater, sampler, &mount).await);
+                    timing.restart();
+                    let _ = SysMode::Disarmed.enter(Reason::Calibrated);
+                }
+                Err(e) => refused("Accel Calibration", e.from),
+            }
+            continue;
+        }
 
-        let imu: _ = get_imu_data(&mut imu, &mut acc_last).await;
-        let (gyro, acc) = imu;
+        if let Some(request) = mounting::requested() {
+            match SysMode::Calibrating.enter(Reason::Calibration) {
+                Ok(()) => {
+                    remount(&mut imu, &mut heater, sampler, &mut mount, request).await;
+                    filter.reset(level(&mut imu, &mut heater, sampler, &mount).await);
+                    timing.restart();
+                    let _ = SysMode::Disarmed.enter(Reason::Calibrated);
+                }
+                Err(e) => refused("Mounting Change", e.from),
+            }
+            continue;
+        }
 
-        // Update AHRS
-        let Ok(_) = ahrs.update_imu(&gyro, &acc) else {
-            defmt::warn!("BMI088 AHRS Update Error!!!");
+        let sample = sampler.next(&mut imu, &mut heater).await;
+        let Some(Sample {
+            time,
+            gyro,
+            acc,
+            acc_time,
+            acc_new,
+        }) = sample
+        else {
             continue;
         };
 
-        // // Output Euler Angles
-        // let (x, y, z) = sta.euler_angles();
-        // let a = (x.to_degrees(), y.to_degrees(), z.to_degrees());
-        // defmt::trace!("Roll={}, Pitch={}, Yaw={}", a.0, a.1, a.2);
+        // Follow Bias Drift while Stationary
+        if bias.track(&gyro, &acc) {
+            defmt::trace!("BMI088 Gyro Bias: {:?}", bias.bias().as_slice());
+        }
+
+        // Sensor to Body Frame
+        let gyro = mount.apply(&bias.correct(&gyro));
+        let acc = mount.apply(&acc);
+
+        // Measured Interval, Nominal after a Stall, FIFO Losses Counted
+        timing.lost(sampler.take_lost());
+        let dt = timing.gyro(time);
+        if acc_new {
+            timing.acc(acc_time);
+        }
+
+        // Update Estimator, Correct with Acc only when Fresh,
+        // each Correction Spans the Accel Period, see `attitude`
+        let quat = utils::measure!(
+            "Filter Update",
+            filter.update(&gyro, acc_new.then_some(&acc), dt)
+        );
+
+        // Output Attitude
+        let body = BodySample {
+            time,
+            acc_time,
+            gyro,
+            acc,
+            temp: heater.temp(),
+        };
+        output::publish(angles.derive(&quat, &body));
     }
 }
 
-async fn get_imu_data(
-    imu: &mut BMI088<'_>,
-    acc_last: &mut (f64, f64, f64),
-) -> (Vector3<f64>, Vector3<f64>) {
-    // Read Gyro Data, And Unit Transform
-    let (x, y, z) = imu.read_gyro().await;
-    let gyro = (x.to_radians(), y.to_radians(), z.to_radians());
-
-    // Read Acc Data, And Unit Transform
-    let (x, y, z) = imu.read_acc().await;
-    let norm = sqrt(x * x + y * y + z * z);
-    let acc = if norm > 1e-3 {
-        (x / norm, y / norm, z / norm)
-    } else {
-        *acc_last
-    };
+///
+/// # Refused Request
+///
+/// The mode changed since the request was accepted.
+///
+fn refused(what: &str, mode: SysMode) {
+    defmt::warn!("BMI088 {=str} Refused in {:?}, Dropped!", what, mode);
+    buzzer::cue(Cue::Failed);
+}
+
+///
+/// # Heater Off on Panic
+///
+/// Safe-state hook, drives TIM3 CH4 low behind the driver's back.
+///
+fn heat_off() {
+    hal::pac::TIM3.ccr(3).write(|w: _| w.set_ccr(0));
+}
+
+///
+/// # Heater Warm Up
+///
+/// Blocks until the temperature settles at the setpoint,
+/// the AHRS must not integrate before that. Gives up with
+/// [`SysMode::Error`] if it does not settle in time, e.g. an open
+/// heater or a cold ambient.
+///
+async fn warm_up(imu: &mut BMI088, heater: &mut Heater<'_, TIM3>, sampler: &mut Sampler) {
+    defmt::info!("BMI088 Heater: Warming Up to {}°C...", heater.setpoint());
+
+    loop {
+        if sampler.next(imu, heater).await.is_none() {
+            continue;
+        }
+
+        match heater.state() {
+            HeatState::WarmingUp => {}
+            HeatState::Stable => return,
+            HeatState::OverTemp => {
+                defmt::error!("BMI088 Heater Failed, Running Unregulated!!!");
+                return;
+            }
+            HeatState::TimedOut => {
+                defmt::error!("BMI088 Heater not Settled, Outputs Stay Off!!!");
+                let _ = SysMode::Error.enter(Reason::Hardware);
+                return;
+            }
+        }
+    }
+}
+
+///
+/// # Startup Gyro Bias Calibration
+///
+/// Blocks until the bias is estimated, or gives up with
+/// [`SysMode::Error`] and a zero bias if the board keeps moving.
+/// The caller leaves [`SysMode::Calibrating`] when done.
+///
+async fn calibrate(
+    imu: &mut BMI088,
+    heater: &mut Heater<'_, TIM3>,
+    bias: &mut GyroBias,
+    sampler: &mut Sampler,
+) {
+    let _ = SysMode::Calibrating.enter(Reason::Calibration);
+    defmt::info!("BMI088 Gyro Calibration: Keep the Board Still...");
 
-    *acc_last = acc;
+    loop {
+        let Some(Sample { gyro, acc, .. }) = sampler.next(imu, heater).await else {
+            continue;
+        };
 
-    (
-        // Return Data: Gyro, Acc
-        Vector3::new(gyro.0, gyro.1, gyro.2),
-        Vector3::new(acc.0, acc.1, acc.2),
+        match bias.startup(&gyro, &acc) {
+            Progress::Collecting => {}
+            Progress::Accepted { accepted } => {
+                defmt::debug!("BMI088 Gyro Calibration: {} Windows Accepted", accepted);
+            }
+            Progress::Rejected { rejected } => {
+                defmt::warn!(
+                    "BMI088 Gyro Calibration: Moving, {} Windows Rejected",
+                    rejected
+                );
+            }
+            Progress::Done => {
+                let b = bias.bias().map(|x: _| x.to_degrees());
+                defmt::info!("BMI088 Gyro Bias (dps): {:?}", b.as_slice());
+                return;
+            }
+            Progress::Failed => {
+                defmt::error!("BMI088 Gyro Calibration Failed!!!");
+                let _ = SysMode::Error.enter(Reason::CalibrationFailed);
+                return;
+            }
+        }
+    }
+}
+
+///
+/// # Initial Attitude
+///
+/// Levels roll and pitch from the next fresh accel sample in the
+/// body frame, zero yaw.
+///
+async fn level(
+    imu: &mut BMI088,
+    heater: &mut Heater<'_, TIM3>,
+    sampler: &mut Sampler,
+    mount: &Mounting,
+) -> UnitQuaternion<f64> {
+    let acc = loop {
+        match sampler.next(imu, heater).await {
+            Some(Sample { acc, acc_new, .. }) if acc_new => break mount.apply(&acc),
+            _ => continue,
+        }
+    };
+
+    UnitQuaternion::from_euler_angles(
+        atan2(acc.y, acc.z),
+        atan2(-acc.x, sqrt(acc.y * acc.y + acc.z * acc.z)),
+        0.,
     )
 }
+
+///
+/// # Guided Six-Position Accel Calibration
+///
+/// Shows the color of each [`Face`] and beeps until the board rests
+/// on it. The result is saved and applied, a failed or implausible
+/// calibration keeps the previous one.
+///
+async fn six_position(
+    imu: &mut BMI088,
+    heater: &mut Heater<'_, TIM3>,
+    sampler: &mut Sampler,
+    cross_axis: bool,
+) {
+    buzzer::cue(Cue::Start);
+    defmt::info!("BMI088 Accel Calibration: Cross-Axis {}", cross_axis);
+
+    let previous = *sampler.acc_calib();
+    sampler.set_acc_calib(AccCalib::IDENTITY);
+
+    let odr = acc_rate(imu);
+    let mut means = [Vector3::zeros(); 6];
+
+    let res = async {
+        for (i, face) in Face::ALL.into_iter().enumerate() {
+            blinky::indicate(Some(face.color()));
+            buzzer::cue(Cue::Next);
+            defmt::info!("BMI088 Accel Calibration: Place the Board {:?}", face);
+
+            let mut pose = Pose::new(face, odr);
+            means[i] = loop {
+                let Some(Sample {
+                    gyro, acc, acc_new, ..
+                }) = sampler.next(imu, heater).await
+                else {
+                    continue;
+                };
+
+                if !acc_new {
+                    continue;
+                }
+
+                match pose.push(&gyro, &acc) {
+                    PoseState::Waiting | PoseState::Holding { .. } => {}
+                    PoseState::Captured(mean) => break mean,
+                    PoseState::Timeout => return None,
+                }
+            };
+
+            buzzer::cue(Cue::Captured);
+            defmt::info!(
+                "BMI088 Accel Calibration: {:?} {:?}",
+                face,
+                means[i].as_slice()
+            );
+        }
+
+        AccCalib::solve(&means, cross_axis)
+    };
+
+    let calib = res.await;
+    blinky::indicate(None);
+
+    let Some(calib) = calib else {
+        defmt::error!("BMI088 Accel Calibration Failed, Previous Kept!!!");
+        sampler.set_acc_calib(previous);
+        buzzer::cue(Cue::Failed);
+        return;
+    };
+
+    let residual = calib.residual(&means);
+    defmt::info!(
+        "BMI088 Accel Calibration: {:?}, Residual {}g",
+        calib,
+        residual
+    );
+
+    sampler.set_acc_calib(calib);
+    buzzer::cue(Cue::Done);
+
+    if let Err(e) = calib.save().await {
+        defmt::error!("BMI088 Accel Calibration not Saved: {:?}", e);
+    }
+}
+
+///
+/// # Apply a Mounting Request
+///
+/// The trim is measured like a calibration pose, resting level for 1s.
+/// The new mounting is saved, a failed trim keeps the previous one.
+///
+async fn remount(
+    imu: &mut BMI088,
+    heater: &mut Heater<'_, TIM3>,
+    sampler: &mut Sampler,
+    mount: &mut Mounting,
+    request: Request,
+) {
+    match request {
+        Request::Mount(orientation) => *mount = Mounting::new(orientation),
+        Request::ClearTrim => mount.set_trim(UnitQuaternion::identity()),
+        Request::Trim => {
+            buzzer::cue(Cue::Start);
+            defmt::info!("BMI088 Level Trim: Rest the Robot on a Level Surface...");
+
+            let mut pose = Pose::new(Face::ZUp, acc_rate(imu));
+            let trim = loop {
+                let Some(Sample {
+                    gyro, acc, acc_new, ..
+                }) = sampler.next(imu, heater).await
+                else {
+                    continue;
+                };
+
+                if !acc_new {
+                    continue;
+                }
+
+                let (gyro, acc) = (mount.untrimmed(&gyro), mount.untrimmed(&acc));
+                match pose.push(&gyro, &acc) {
+                    PoseState::Waiting | PoseState::Holding { .. } => {}
+                    PoseState::Captured(mean) => break Mounting::solve_trim(&mean),
+                    PoseState::Timeout => break None,
+                }
+            };
+
+            let Some(trim) = trim else {
+                defmt::error!("BMI088 Level Trim Failed, Previous Kept!!!");
+                buzzer::cue(Cue::Failed);
+                return;
+            };
+
+            mount.set_trim(trim);
+            defmt::info!("BMI088 Level Trim: {}°", mount.trim().angle().to_degrees());
+            buzzer::cue(Cue::Done);
+        }
+    }
+
+    defmt::info!("BMI088 Mounting: {:?}", mount);
+
+    if let Err(e) = mount.save().await {
+        defmt::error!("BMI088 Mounting not Saved: {:?}", e);
+    }
+}
+
+///
+/// # Fresh Accel Rate in Hz
+///
+/// Without its interrupt the accel is read with every gyro sample.
+///
+fn acc_rate(imu: &BMI088) -> f64 {
+    let c = imu.config();
+    match c.acc_drdy || c.fifo.is_some() {
+        true => 1. / c.acc_period(),
+        false => c.gyro_odr(),
+    }
+}
@@ -0,0 +1,308 @@
+//!
+//! # Mounting Orientation
+//!
+//! Rotates sensor frame samples into the body frame before fusion:
+//! `body = trim · rotation · sensor`.
+//!
+//! - `rotation`: how the board is mounted, a preset 90° [`Rotation`]
+//!   or any quaternion.
+//! - `trim`: small level correction measured with the robot resting on
+//!   a level surface, so that gravity reads straight along body +Z.
+//!
+//! The setting is kept in [`utils::storage`] and takes precedence over
+//! the compiled default once changed at runtime, e.g. from the
+//! console. Changing the rotation clears the trim, it was measured for
+//! the previous one.
+//!
+//! ## Change the Mounting
+//! ```rust
+//! bmi088::set_mounting(Orientation::Preset(Rotation::Roll180));
+//! bmi088::set_mounting(Orientation::Custom(quat));
+//! ```
+//!
+//! ## Level Trim
+//! ```rust
+//! bmi088::level_trim(); // Robot Resting on a Level Surface
+//! bmi088::clear_trim();
+//! ```
+//!
+
+use crate::sync;
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, signal::Signal};
+use utils::storage::{self, StorageError};
+
+/// Storage Slot of the Mounting
+const SLOT: usize = 1;
+/// Record Layout Version
+const VERSION: u8 = 1;
+/// Record: Version + Rotation(4) + Trim(4) as f64
+const RECORD: usize = 1 + 8 * 8;
+
+/// Maximum Trim Angle in rad (10°), more means a Wrong Rotation
+pub const TRIM_MAX: f64 = 0.1745;
+
+static REQUEST: Signal<RM, Request> = Signal::new();
+
+///
+/// # Mounting Request
+///
+#[derive(Clone, Copy, defmt::Format)]
+pub enum Request {
+    /// Set the Rotation, Clears the Trim
+    Mount(Orientation),
+    /// Measure the Trim on a Level Surface
+    Trim,
+    /// Clear the Trim
+    ClearTrim,
+}
+
+///
+/// # Set Mounting Orientation
+///
+pub fn set_mounting(orientation: Orientation) {
+    REQUEST.signal(Request::Mount(orientation));
+}
+
+///
+/// # Measure Level Trim
+///
+pub fn level_trim() {
+    REQUEST.signal(Request::Trim);
+}
+
+///
+/// # Clear Level Trim
+///
+pub fn clear_trim() {
+    REQUEST.signal(Request::ClearTrim);
+}
+
+///
+/// # Take a Pending Request
+///
+pub(super) fn requested() -> Option<Request> {
+    REQUEST.try_take()
+}
+
+///
+/// # Preset Rotation
+///
+/// Rotation from the sensor to the body frame, as intrinsic
+/// roll about X, then pitch about Y, then yaw about Z.
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub enum Rotation {
+    None,
+    Yaw90,
+    Yaw180,
+    Yaw270,
+    Roll90,
+    Roll180,
+    Roll270,
+    Pitch90,
+    Pitch180,
+    Pitch270,
+    Roll180Yaw90,
+    Roll180Yaw270,
+    Roll90Yaw90,
+    Roll270Yaw90,
+}
+
+impl Rotation {
+    ///
+    /// # Preset by Name
+    ///
+    /// The variant name in lower case, e.g. `roll180yaw90`.
+    ///
+    pub fn from_name(name: &str) -> Option<Self> {
+        let rotation = match name {
+            "none" => Self::None,
+            "yaw90" => Self::Yaw90,
+            "yaw180" => Self::Yaw180,
+            "yaw270" => Self::Yaw270,
+            "roll90" => Self::Roll90,
+            "roll180" => Self::Roll180,
+            "roll270" => Self::Roll270,
+            "pitch90" => Self::Pitch90,
+            "pitch180" => Self::Pitch180,
+            "pitch270" => Self::Pitch270,
+            "roll180yaw90" => Self::Roll180Yaw90,
+            "roll180yaw270" => Self::Roll180Yaw270,
+            "roll90yaw90" => Self::Roll90Yaw90,
+            "roll270yaw90" => Self::Roll270Yaw90,
+            _ => return None,
+        };
+        Some(rotation)
+    }
+
+    ///
+    /// # Roll, Pitch, Yaw in Degrees
+    ///
+    pub const fn euler(self) -> (f64, f64, f64) {
+        match self {
+            Self::None => (0., 0., 0.),
+            Self::Yaw90 => (0., 0., 90.),
+            Self::Yaw180 => (0., 0., 180.),
+            Self::Yaw270 => (0., 0., 270.),
+            Self::Roll90 => (90., 0., 0.),
+            Self::Roll180 => (180., 0., 0.),
+            Self::Roll270 => (270., 0., 0.),
+            Self::Pitch90 => (0., 90., 0.),
+            Self::Pitch180 => (0., 180., 0.),
+            Self::Pitch270 => (0., 270., 0.),
+            Self::Roll180Yaw90 => (180., 0., 90.),
+            Self::Roll180Yaw270 => (180., 0., 270.),
+            Self::Roll90Yaw90 => (90., 0., 90.),
+            Self::Roll270Yaw90 => (270., 0., 90.),
+        }
+    }
+
+    ///
+    /// # Rotation Quaternion
+    ///
+    pub fn quat(self) -> UnitQuaternion<f64> {
+        let (r, p, y) = self.euler();
+        UnitQuaternion::from_euler_angles(r.to_radians(), p.to_radians(), y.to_radians())
+    }
+}
+
+///
+/// # Mounting Orientation
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub enum Orientation {
+    /// 90° Preset
+    Preset(Rotation),
+    /// Arbitrary Sensor to Body Rotation
+    Custom(UnitQuaternion<f64>),
+}
+
+impl Orientation {
+    ///
+    /// # Rotation Quaternion
+    ///
+    pub fn quat(self) -> UnitQuaternion<f64> {
+        match self {
+            Self::Preset(r) => r.quat(),
+            Self::Custom(q) => q,
+        }
+    }
+}
+
+///
+/// # Sensor to Body Transform
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub struct Mounting {
+    rotation: UnitQuaternion<f64>,
+    trim: UnitQuaternion<f64>,
+    body: UnitQuaternion<f64>,
+}
+
+impl Mounting {
+    ///
+    /// # New Mounting without Trim
+    ///
+    pub fn new(orientation: Orientation) -> Self {
+        let rotation = orientation.quat();
+        Self {
+            rotation,
+            trim: UnitQuaternion::identity(),
+            body: rotation,
+        }
+    }
+
+    ///
+    /// # Get Trim Rotation
+    ///
+    pub fn trim(&self) -> UnitQuaternion<f64> {
+        self.trim
+    }
+
+    ///
+    /// # Set Trim Rotation
+    ///
+    pub fn set_trim(&mut self, trim: UnitQuaternion<f64>) {
+        self.trim = trim;
+        self.body = trim * self.rotation;
+    }
+
+    ///
+    /// # Untrimmed Sensor to Body Rotation
+    ///
+    /// Frame the trim is measured in.
+    ///
+    pub fn untrimmed(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.rotation * v
+    }
+
+    ///
+    /// # Sensor to Body
+    ///
+    pub fn apply(&self, v: &Vector3<f64>) -> Vector3<f64> {
+        self.body * v
+    }
+
+    ///
+    /// # Solve Trim
+    ///
+    /// `acc` is the mean untrimmed body accel at rest on a level
+    /// surface. Returns `None` if more than [`TRIM_MAX`] off level.
+    ///
+    pub fn solve_trim(acc: &Vector3<f64>) -> Option<UnitQuaternion<f64>> {
+        let trim = UnitQuaternion::rotation_between(acc, &Vector3::z())?;
+        (trim.angle() <= TRIM_MAX).then_some(trim)
+    }
+
+    ///
+    /// # Load from Storage
+    ///
+    pub async fn load() -> Option<Self> {
+        let mut buf = [0; RECORD];
+        let len = storage::load(SLOT, &mut buf).await?;
+        Self::from_bytes(&buf[..len])
+    }
+
+    ///
+    /// # Save to Storage
+    ///
+    pub async fn save(&self) -> Result<(), StorageError> {
+        storage::store(SLOT, &self.to_bytes()).await
+    }
+
+    fn to_bytes(self) -> [u8; RECORD] {
+        let mut buf = [0; RECORD];
+        buf[0] = VERSION;
+
+        let values = self.rotation.coords.iter().chain(self.trim.coords.iter());
+        for (chunk, v) in buf[1..].as_chunks_mut::<8>().0.iter_mut().zip(values) {
+            chunk.copy_from_slice(&v.to_le_bytes());
+        }
+
+        buf
+    }
+
+    fn from_bytes(buf: &[u8]) -> Option<Self> {
+        let [VERSION, data @ ..] = buf else {
+            return None;
+        };
+
+        if data.len() != RECORD - 1 {
+            return None;
+        }
+
+        let mut v = [0.; 8];
+        for (x, chunk) in v.iter_mut().zip(data.as_chunks::<8>().0) {
+            *x = f64::from_le_bytes(*chunk);
+        }
+
+        // Coordinates are Stored as [i, j, k, w]
+        let quat =
+            |c: &[f64]| UnitQuaternion::try_new(Quaternion::new(c[3], c[0], c[1], c[2]), 1e-6);
+
+        let mut mounting = Self::new(Orientation::Custom(quat(&v[..4])?));
+        mounting.set_trim(quat(&v[4..])?);
+        Some(mounting)
+    }
+}
@@ -0,0 +1,90 @@
+//!
+//! # IMU Output
+//!
+//! Publication point of the fused attitude and the raw samples.
+//!
+//! ## Wait for the Next Sample
+//! ```rust
+//! let mut rx = bmi088::receiver().unwrap();
+//! let data: ImuData = rx.changed().await;
+//! ```
+//!
+//! ## Get the Latest Sample
+//! ```rust
+//! let data: Option<ImuData> = bmi088::latest();
+//! ```
+//!
+
+use crate::{sync, time::Instant};
+use attitude::RotationOrder;
+use nalgebra::{UnitQuaternion, Vector3};
+use sync::blocking_mutex::raw::CriticalSectionRawMutex as RM;
+use sync::watch::{Receiver, Watch};
+
+/// Maximum Number of Concurrent Receivers
+const RECEIVERS: usize = 4;
+
+static OUTPUT: Watch<RM, ImuData, RECEIVERS> = Watch::new();
+
+///
+/// # IMU Data
+///
+/// Vectors are in the body frame unless named world, see the
+/// mounting orientation. The world Z axis points up.
+///
+#[derive(Clone, Copy, defmt::Format)]
+pub struct ImuData {
+    /// Gyro Sample Time
+    pub time: Instant,
+    /// Acc Sample Time
+    pub acc_time: Instant,
+    /// Attitude Quaternion, Body to World, Yaw Zeroed
+    pub quat: UnitQuaternion<f64>,
+    /// Euler Angles in `order`: Roll, Pitch, Yaw in rad
+    pub euler: Vector3<f64>,
+    /// Rotation Order of `euler`
+    pub order: RotationOrder,
+    /// Continuous Yaw in rad, not Wrapped
+    pub yaw: f64,
+    /// Whole Turns of `yaw`, Rounded towards Zero
+    pub turns: i32,
+    /// Bias Corrected Angular Rates in rad/s
+    pub gyro: Vector3<f64>,
+    /// Angular Rates in the World Frame in rad/s
+    pub gyro_world: Vector3<f64>,
+    /// Specific Force, Gravity Included, in m/s²
+    pub acc: Vector3<f64>,
+    /// Gravity Compensated Acceleration in m/s²
+    pub linear_acc: Vector3<f64>,
+    /// Gravity Compensated Acceleration in the World Frame in m/s²
+    pub linear_acc_world: Vector3<f64>,
+    /// Sensor Temperature in °C
+    pub temp: f32,
+}
+
+///
+/// # Publish IMU Data
+///
+/// Never blocks, receivers that fall behind only see the latest sample.
+///
+pub(super) fn publish(data: ImuData) {
+    OUTPUT.sender().send(data);
+}
+
+///
+/// # Get Receiver
+///
+/// Returns `None` if all [`RECEIVERS`] are taken.
+///
+pub fn receiver() -> Option<Receiver<'static, RM, ImuData, RECEIVERS>> {
+    OUTPUT.receiver()
+}
+
+///
+/// # Get Latest Sample
+///
+/// Returns `None` before the first sample is published.
+///
+pub fn latest() -> Option<ImuData> {
+    OUTPUT.try_get()
+}
@@ -0,0 +1,413 @@
+//!
+//! # BMI088 Sampler
+//!
+//! Yields timestamped samples one at a time, either read on every
+//! data ready interrupt and stamped as soon as the edge wakes the
+//! task, or unpacked from the FIFOs on every watermark interrupt.
+//! FIFO frames are timestamped backwards from the watermark wake, taken
+//! before the FIFOs are read, at the output data rate, and each gyro
+//! frame is paired with the latest accel frame not newer than itself.
+//! Frames lost by the FIFOs are counted from the [`FifoStats`].
+//!
+//! With the accel data ready interrupt the accel is only read when
+//! fresh, otherwise it is read along with every gyro sample.
+//!
+//! The accel calibration is applied to every accel sample.
+//!
+//! Every SPI transaction is timed by a probe, see [`utils::probe`].
+//!
+//! Every raw sample also passes the fault [`Monitor`]: the heartbeat
+//! is only fed while the data is plausible, so clipping, stuck values
+//! or a lasting implausible accel norm take the device offline.
+//!
+
+use super::acc_calib::AccCalib;
+use super::heater::Heater;
+use super::typedef::{BMI088, Bmi088Error};
+use crate::time::{Duration, Instant};
+use crate::{hal::peripherals::TIM3, sync, system::*};
+use bmi088::fifo::{ACC_FIFO_BYTES, GYRO_FIFO_FRAMES};
+use bmi088::{DataReady, Faults, FifoStats, Monitor, MonitorReport};
+use core::cell::Cell;
+use nalgebra::Vector3;
+use sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+
+/// Consecutive Sample Errors before the Device is Offline
+const ERROR_MAX: u32 = 100;
+/// Consecutive Sample Errors before the Heater is Cut
+const HEAT_ERROR_MAX: u32 = 5;
+
+/// Accel FIFO Capacity in Frames: 1 Header + 6 Data Bytes
+const ACC_FIFO_FRAMES: usize = ACC_FIFO_BYTES / 7;
+
+static REPORT: Mutex<RM, Cell<Option<MonitorReport>>> = Mutex::new(Cell::new(None));
+
+///
+/// # Get Sensor Health
+///
+/// Returns the latest monitor window, `None` before the first.
+///
+pub fn sensor_health() -> Option<MonitorReport> {
+    REPORT.lock(|r: _| r.get())
+}
+
+///
+/// # IMU Sample
+///
+#[derive(Clone, Copy)]
+pub struct Sample {
+    /// Gyro Sample Time
+    pub time: Instant,
+    /// Gyro in rad/s
+    pub gyro: Vector3<f64>,
+    /// Latest Calibrated Acc in g
+    pub acc: Vector3<f64>,
+    /// Acc Sample Time
+    pub acc_time: Instant,
+    /// Acc not Seen by a Previous Sample
+    pub acc_new: bool,
+}
+
+///
+/// # FIFO Batch
+///
+/// Frames of one watermark interrupt, oldest first.
+///
+struct Batch {
+    time: Instant,
+    gyro: [[f64; 3]; GYRO_FIFO_FRAMES],
+    gyro_len: usize,
+    gyro_next: usize,
+    acc: [[f64; 3]; ACC_FIFO_FRAMES],
+    acc_len: usize,
+    acc_next: usize,
+}
+
+pub struct Sampler {
+    errors: u32,
+    batch: Batch,
+    stats: FifoStats,
+    lost: FifoStats,
+    calib: AccCalib,
+    monitor: Option<Monitor>,
+    faults: Faults,
+
+    acc: Vector3<f64>,
+    acc_time: Instant,
+    acc_new: bool,
+}
+
+impl Sampler {
+    pub const fn new() -> Self {
+        let batch = Batch {
+            time: Instant::MIN,
+            gyro: [[0.; 3]; _],
+            gyro_len: 0,
+            gyro_next: 0,
+            acc: [[0.; 3]; _],
+            acc_len: 0,
+            acc_next: 0,
+        };
+
+        Self {
+            errors: 0,
+            batch,
+            stats: FifoStats {
+                gyro_overruns: 0,
+                acc_skipped: 0,
+                dropped: 0,
+            },
+            lost: FifoStats {
+                gyro_overruns: 0,
+                acc_skipped: 0,
+                dropped: 0,
+            },
+            calib: AccCalib::IDENTITY,
+            monitor: None,
+            faults: Faults {
+                gyro_stuck: false,
+                acc_stuck: false,
+                gyro_clipping: false,
+                acc_clipping: false,
+                acc_norm: false,
+            },
+            acc: Vector3::new(0., 0., 0.),
+            acc_time: Instant::MIN,
+            acc_new: false,
+        }
+    }
+}
+
+impl Sampler {
+    ///
+    /// # Get Accel Calibration
+    ///
+    pub fn acc_calib(&self) -> &AccCalib {
+        &self.calib
+    }
+
+    ///
+    /// # Set Accel Calibration
+    ///
+    /// Applies to accel samples read from now on.
+    ///
+    pub fn set_acc_calib(&mut self, calib: AccCalib) {
+        self.calib = calib;
+    }
+
+    ///
+    /// # Set Fault Monitor
+    ///
+    pub fn set_monitor(&mut self, monitor: Monitor) {
+        self.monitor = Some(monitor);
+    }
+
+    ///
+    /// # Take Lost FIFO Frames
+    ///
+    /// The FIFO losses since the previous call.
+    ///
+    pub fn take_lost(&mut self) -> FifoStats {
+        core::mem::take(&mut self.lost)
+    }
+
+    ///
+    /// # Next Sample
+    ///
+    /// Waits for new data when needed and runs the heater.
+    ///
+    /// Checks in with the watchdog on every call.
+    ///
+    /// Feeds the heartbeat on a fault free success, logs the first error
+    /// of a run and marks the device offline after `ERROR_MAX`
+    /// consecutive errors. The heater is cut after `HEAT_ERROR_MAX`
+    /// consecutive errors, or when its temperature went stale.
+    ///
+    pub async fn next(
+        &mut self,
+        imu: &mut BMI088,
+        heater: &mut Heater<'_, TIM3>,
+    ) -> Option<Sample> {
+        Task::Bmi088.check_in();
+        heater.watch();
+
+        let res = async {
+            let (time, gyro) = match imu.config().fifo {
+                Some(_) => self.pop(imu).await?,
+                None => self.read(imu).await?,
+            };
+
+            regulate(imu, heater).await?;
+
+            let sample = Sample {
+                time,
+                gyro,
+                acc: self.acc,
+                acc_time: self.acc_time,
+                acc_new: self.acc_new,
+            };
+            self.acc_new = false;
+            Ok::<_, Bmi088Error>(sample)
+        };
+
+        match res.await {
+            Ok(x) => {
+                if self.errors >= ERROR_MAX {
+                    defmt::info!("BMI088 Recovered after {} Errors", self.errors);
+                }
+
+                self.errors = 0;
+                self.check_faults();
+                Some(x)
+            }
+            Err(e) => {
+                self.errors = self.errors.saturating_add(1);
+                match self.errors {
+                    1 => defmt::warn!("BMI088 Sample Failed: {:?}", e),
+                    HEAT_ERROR_MAX => {
+                        defmt::warn!("BMI088 Heater Off after {} Errors!", HEAT_ERROR_MAX)
+                    }
+                    ERROR_MAX => {
+                        defmt::error!(
+                            "BMI088 Sample Failed {} Times, Device Offline!!!",
+                            ERROR_MAX
+                        );
+                        Device::Bmi088.kill();
+                    }
+                    _ => {}
+                }
+
+                // Never leave the Heater at its Last Duty Blind
+                if self.errors >= HEAT_ERROR_MAX {
+                    heater.off();
+                }
+                None
+            }
+        }
+    }
+
+    ///
+    /// # Pop FIFO Sample
+    ///
+    /// Refills the batch from the FIFOs when it is used up.
+    ///
+    async fn pop(&mut self, imu: &mut BMI088) -> Result<(Instant, Vector3<f64>), Bmi088Error> {
+        while self.batch.gyro_next >= self.batch.gyro_len {
+            self.refill(imu).await?;
+        }
+
+        let c = imu.config();
+        let b = &mut self.batch;
+
+        let i = b.gyro_next;
+        b.gyro_next += 1;
+        let time = back(b.time, b.gyro_len - 1 - i, c.gyro_period());
+
+        while self.batch.acc_next < self.batch.acc_len {
+            let b = &mut self.batch;
+            let acc_time = back(b.time, b.acc_len - 1 - b.acc_next, c.acc_period());
+            if acc_time > time {
+                break;
+            }
+
+            let mg = b.acc[b.acc_next];
+            b.acc_next += 1;
+            self.push_acc(mg, acc_time);
+        }
+
+        Ok((time, self.push_gyro(self.batch.gyro[i])))
+    }
+
+    ///
+    /// # Read Data Ready Sample
+    ///
+    /// Reads the accel on its own interrupt if enabled,
+    /// otherwise along with the gyro.
+    ///
+    async fn read(&mut self, imu: &mut BMI088) -> Result<(Instant, Vector3<f64>), Bmi088Error> {
+        loop {
+            let ready = imu.wait_data().await?;
+            let time = Instant::now();
+
+            if ready == DataReady::Acc || !imu.config().acc_drdy {
+                let (x, y, z) = utils::measure!("BMI088 SPI Acc", imu.read_acc().await?);
+                self.push_acc([x, y, z], time);
+            }
+
+            if ready == DataReady::Gyro {
+                let (x, y, z) = utils::measure!("BMI088 SPI Gyro", imu.read_gyro().await?);
+                return Ok((time, self.push_gyro([x, y, z])));
+            }
+        }
+    }
+
+    ///
+    /// # Accept Raw Accel in mg
+    ///
+    /// Monitors, calibrates and converts to g.
+    ///
+    fn push_acc(&mut self, mg: [f64; 3], time: Instant) {
+        let report = self.monitor.as_mut().and_then(|m: _| m.acc(mg));
+        if let Some(report) = report {
+            REPORT.lock(|r: _| r.set(Some(report)));
+            defmt::trace!("BMI088 Monitor: {:?}", report);
+        }
+
+        self.acc = self.calib.apply(&(Vector3::from(mg) / 1000.));
+        self.acc_time = time;
+        self.acc_new = true;
+    }
+
+    ///
+    /// # Accept Raw Gyro in dps
+    ///
+    /// Monitors and converts to rad/s.
+    ///
+    fn push_gyro(&mut self, dps: [f64; 3]) -> Vector3<f64> {
+        if let Some(m) = self.monitor.as_mut() {
+            m.gyro(dps);
+        }
+
+        Vector3::from(dps.map(f64::to_radians))
+    }
+
+    ///
+    /// # Feed Heartbeat unless Faulty
+    ///
+    /// Logs every change of the active faults.
+    ///
+    fn check_faults(&mut self) {
+        let faults = self
+            .monitor
+            .as_ref()
+            .map(Monitor::faults)
+            .unwrap_or_default();
+
+        if faults != self.faults {
+            match faults.any() {
+                true => defmt::error!("BMI088 Data Faulty, not Fed: {:?}", faults),
+                false => defmt::info!("BMI088 Data Faults Cleared"),
+            }
+            self.faults = faults;
+        }
+
+        if !faults.any() {
+            Device::Bmi088.feed();
+        }
+    }
+
+    async fn refill(&mut self, imu: &mut BMI088) -> Result<(), Bmi088Error> {
+        let b = &mut self.batch;
+        (b.gyro_len, b.gyro_next) = (0, 0);
+        (b.acc_len, b.acc_next) = (0, 0);
+
+        // Stamp at the Wake, the SPI Reads would Skew the Frame Times
+        imu.wait_new_data().await?;
+        b.time = Instant::now();
+
+        let gyro = utils::measure!(
+            "BMI088 SPI Gyro FIFO",
+            imu.read_gyro_fifo(&mut b.gyro).await?
+        );
+        let acc = utils::measure!("BMI088 SPI Acc FIFO", imu.read_acc_fifo(&mut b.acc).await?);
+        (b.gyro_len, b.acc_len) = (gyro.frames, acc.frames);
+
+        let stats = *imu.fifo_stats();
+        if stats != self.stats {
+            defmt::warn!("BMI088 FIFO Frames Lost: {:?}", stats);
+            let (l, s) = (&mut self.lost, &self.stats);
+            l.gyro_overruns += stats.gyro_overruns.saturating_sub(s.gyro_overruns);
+            l.acc_skipped += stats.acc_skipped.saturating_sub(s.acc_skipped);
+            l.dropped += stats.dropped.saturating_sub(s.dropped);
+            self.stats = stats;
+        }
+
+        Ok(())
+    }
+}
+
+///
+/// # Heater Control Step
+///
+/// Call once per gyro sample, reads the temperature
+/// and updates the heater every control period.
+///
+async fn regulate(imu: &mut BMI088, heater: &mut Heater<'_, TIM3>) -> Result<(), Bmi088Error> {
+    if heater.due() {
+        // The temperature sensor data is updated every 1.28s
+        let temp = utils::measure!("BMI088 SPI Temp", imu.read_temp().await?);
+        heater.update(temp);
+    }
+
+    Ok(())
+}
+
+///
+/// # Timestamp Backwards
+///
+/// Time of the frame `n` periods before `time`.
+///
+fn back(time: Instant, n: usize, period: f64) -> Instant {
+    let ago = Duration::from_micros((n as f64 * period * 1e6) as u64);
+    time.checked_sub(ago).unwrap_or(Instant::MIN)
+}
@@ -0,0 +1,279 @@
+//!
+//! # Sample Timing
+//!
+//! Measures the interval between sample timestamps, which the filter
+//! integrates instead of the nominal period, and keeps per-window
+//! statistics of the real output data rate, the jitter and the missed
+//! samples. The time base ticks at 1MHz.
+//!
+//! Read on data ready, a gap of more than one period has missed edges.
+//! Read from the FIFOs, frames are stamped at the nominal period and
+//! the gaps say nothing, the losses reported by the FIFOs are counted
+//! instead.
+//!
+//! ## Query the Latest Window
+//! ```rust
+//! let stats = bmi088::timing();
+//! ```
+//!
+
+use crate::sync;
+use crate::time::{Duration, Instant};
+use bmi088::FifoStats;
+use core::cell::Cell;
+use sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+
+/// Statistics Window
+const WINDOW: Duration = Duration::from_secs(10);
+
+/// An Interval Longer than this many Periods has Missed Edges
+const MISSED: f64 = 1.5;
+/// Longest Interval Integrated in Periods, Longer Ones are Stalls
+const DT_MAX: f64 = 5.;
+
+static STATS: Mutex<RM, Cell<TimingStats>> = Mutex::new(Cell::new(TimingStats::new()));
+
+///
+/// # Rate Statistics of one Window
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub struct RateStats {
+    /// Measured Output Data Rate in Hz
+    pub odr: f32,
+    /// Shortest Interval in µs
+    pub dt_min: u32,
+    /// Longest Interval in µs
+    pub dt_max: u32,
+    /// Interval Standard Deviation in µs
+    pub jitter: f32,
+    /// Missed Edges in the Window, or FIFO Losses: Gyro Overruns and
+    /// Lost Accel Frames
+    pub missed: u32,
+    /// Missed since Boot
+    pub missed_total: u32,
+}
+
+impl RateStats {
+    const fn new() -> Self {
+        Self {
+            odr: 0.,
+            dt_min: 0,
+            dt_max: 0,
+            jitter: 0.,
+            missed: 0,
+            missed_total: 0,
+        }
+    }
+}
+
+///
+/// # Timing Statistics
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub struct TimingStats {
+    pub gyro: RateStats,
+    pub acc: RateStats,
+}
+
+impl TimingStats {
+    const fn new() -> Self {
+        Self {
+            gyro: RateStats::new(),
+            acc: RateStats::new(),
+        }
+    }
+}
+
+///
+/// # Get Timing Statistics
+///
+/// Returns the latest completed window, zeroed before the first.
+///
+pub fn timing() -> TimingStats {
+    STATS.lock(|s: _| s.get())
+}
+
+///
+/// # Interval Accumulator
+///
+struct Rate {
+    period: f64,
+    /// Count Missed Edges from the Gaps, not in FIFO Mode
+    gaps: bool,
+    last: Option<Instant>,
+    n: u32,
+    sum: f64,
+    sum2: f64,
+    min: u64,
+    max: u64,
+    missed: u32,
+    missed_total: u32,
+}
+
+impl Rate {
+    fn new(period: f64, gaps: bool) -> Self {
+        Self {
+            period,
+            gaps,
+            last: None,
+            n: 0,
+            sum: 0.,
+            sum2: 0.,
+            min: u64::MAX,
+            max: 0,
+            missed: 0,
+            missed_total: 0,
+        }
+    }
+
+    ///
+    /// # Push Timestamp
+    ///
+    /// Returns the interval to integrate in s, the nominal period on
+    /// the first sample, a reordered sample or a stall.
+    ///
+    fn push(&mut self, time: Instant) -> f64 {
+        let last = self.last.replace(time);
+        let Some(us) = last.and_then(|l: _| time.checked_duration_since(l)) else {
+            return self.period;
+        };
+
+        let us = us.as_micros();
+        let dt = us as f64 * 1e-6;
+
+        self.n += 1;
+        self.sum += dt;
+        self.sum2 += dt * dt;
+        self.min = self.min.min(us);
+        self.max = self.max.max(us);
+
+        let periods = dt / self.period;
+        if self.gaps && periods > MISSED {
+            self.lose(libm::round(periods) as u32 - 1);
+        }
+
+        match dt > 0. && periods <= DT_MAX {
+            true => dt,
+            false => self.period,
+        }
+    }
+
+    ///
+    /// # Count Missed Samples
+    ///
+    fn lose(&mut self, n: u32) {
+        self.missed = self.missed.saturating_add(n);
+        self.missed_total = self.missed_total.saturating_add(n);
+    }
+
+    ///
+    /// # Take Window Statistics
+    ///
+    fn take(&mut self) -> RateStats {
+        let n = self.n.max(1) as f64;
+        let mean = self.sum / n;
+        let var = (self.sum2 / n - mean * mean).max(0.);
+
+        let stats = RateStats {
+            odr: match self.sum > 0. {
+                true => (n / self.sum) as f32,
+                false => 0.,
+            },
+            dt_min: self.min.min(self.max) as u32,
+            dt_max: self.max as u32,
+            jitter: (libm::sqrt(var) * 1e6) as f32,
+            missed: self.missed,
+            missed_total: self.missed_total,
+        };
+
+        *self = Self {
+            last: self.last,
+            missed_total: self.missed_total,
+            ..Self::new(self.period, self.gaps)
+        };
+
+        stats
+    }
+}
+
+///
+/// # Sample Timing
+///
+pub struct Timing {
+    gyro: Rate,
+    acc: Rate,
+    start: Instant,
+}
+
+impl Timing {
+    ///
+    /// # New Timing
+    ///
+    /// Nominal gyro and accel periods in s, `fifo` if the samples
+    /// are read from the FIFOs.
+    ///
+    pub fn new(gyro_period: f64, acc_period: f64, fifo: bool) -> Self {
+        Self {
+            gyro: Rate::new(gyro_period, !fifo),
+            acc: Rate::new(acc_period, !fifo),
+            start: Instant::now(),
+        }
+    }
+
+    ///
+    /// # Gyro Sample
+    ///
+    /// Returns the interval since the previous gyro sample in s,
+    /// and publishes the statistics at the end of every window.
+    ///
+    pub fn gyro(&mut self, time: Instant) -> f64 {
+        let dt = self.gyro.push(time);
+
+        if time
+            .checked_duration_since(self.start)
+            .is_some_and(|d: _| d >= WINDOW)
+        {
+            self.start = time;
+            let stats = TimingStats {
+                gyro: self.gyro.take(),
+                acc: self.acc.take(),
+            };
+
+            STATS.lock(|s: _| s.set(stats));
+            match stats.gyro.missed + stats.acc.missed {
+                0 => defmt::debug!("BMI088 Timing: {:?}", stats),
+                _ => defmt::warn!("BMI088 Timing, Samples Missed: {:?}", stats),
+            }
+        }
+
+        dt
+    }
+
+    ///
+    /// # Fresh Accel Sample
+    ///
+    pub fn acc(&mut self, time: Instant) {
+        self.acc.push(time);
+    }
+
+    ///
+    /// # FIFO Losses
+    ///
+    /// Counts the losses reported since the previous call.
+    ///
+    pub fn lost(&mut self, lost: FifoStats) {
+        self.gyro.lose(lost.gyro_overruns);
+        self.acc.lose(lost.acc_skipped.saturating_add(lost.dropped));
+    }
+
+    ///
+    /// # Restart
+    ///
+    /// Forget the previous timestamps after a pause in sampling,
+    /// the next interval is nominal.
+    ///
+    pub fn restart(&mut self) {
+        self.gyro.last = None;
+        self.acc.last = None;
+    }
+}
@@ -0,0 +1,64 @@
+//!
+//! # Estimator Tuning
+//!
+//! Runtime selection and gains of the attitude filter, applied by the
+//! IMU task before the next sample. The filter at boot is chosen by the
+//! `mahony` (default), `madgwick` or `eskf` cargo feature.
+//!
+//! ## Switch Filter
+//! ```rust
+//! bmi088::select(Kind::Eskf);
+//! ```
+//!
+//! ## Tune Gains
+//! ```rust
+//! bmi088::tune(Gains::Mahony(MahonyGains { kp: 2., ki: 0.01 }));
+//! ```
+//!
+
+use crate::sync;
+use attitude::{Filter, Gains, Kind};
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, signal::Signal};
+
+/// Filter at Boot, Selected by Cargo Feature
+pub const BOOT_FILTER: Kind = match () {
+    _ if cfg!(feature = "eskf") => Kind::Eskf,
+    _ if cfg!(feature = "madgwick") => Kind::Madgwick,
+    _ => Kind::Mahony,
+};
+
+static SELECT: Signal<RM, Kind> = Signal::new();
+static TUNE: Signal<RM, Gains> = Signal::new();
+
+///
+/// # Select Filter
+///
+/// Switches to `kind` with its default gains, keeping the attitude.
+///
+pub fn select(kind: Kind) {
+    SELECT.signal(kind);
+}
+
+///
+/// # Tune Filter
+///
+/// Applies `gains`, switching the filter if they belong to another.
+///
+pub fn tune(gains: Gains) {
+    TUNE.signal(gains);
+}
+
+///
+/// # Apply Pending Requests
+///
+pub(super) fn apply(filter: &mut Filter) {
+    if let Some(kind) = SELECT.try_take() {
+        filter.switch(kind);
+        defmt::info!("BMI088 Estimator: {:?}", kind);
+    }
+
+    if let Some(gains) = TUNE.try_take() {
+        filter.tune(gains);
+        defmt::info!("BMI088 Estimator Gains: {:?}", gains);
+    }
+}
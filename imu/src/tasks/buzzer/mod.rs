@@ -0,0 +1,124 @@
+//!
+//! # Buzzer Task
+//!
+//! Plays short operator cues on request, one at a time.
+//! A cue requested while another plays replaces any pending one.
+//!
+//! Also sounds the alarm when a device that matters goes offline,
+//! and the all clear when it recovers.
+//!
+//! ## Request a Cue
+//! ```rust
+//! buzzer::cue(Cue::Done);
+//! ```
+//!
+
+use crate::hal::{gpio, time::hz, timer};
+use crate::{ef, sync, system::*};
+
+use ef::select::{Either, select};
+use utils::devices::{Criticality, Event, Transition};
+
+use gpio::OutputType::PushPull as Mode;
+use low_level::CountingMode::EdgeAlignedUp;
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, signal::Signal};
+use timer::simple_pwm::{PwmPin, SimplePwm};
+use timer::{Channel, low_level};
+
+mod typedef;
+
+use typedef::Buzzer;
+
+static CUE: Signal<RM, Cue> = Signal::new();
+
+///
+/// # Operator Cue
+///
+#[derive(Clone, Copy, PartialEq, defmt::Format)]
+pub enum Cue {
+    /// Procedure Started
+    Start,
+    /// Move to the Next Pose
+    Next,
+    /// Pose Captured
+    Captured,
+    /// Procedure Succeeded
+    Done,
+    /// Procedure Failed
+    Failed,
+}
+
+impl Cue {
+    /// Notes as (Frequency in Hz, Duration in ms), 0Hz is a Rest
+    const fn tone(self) -> &'static [(u32, u16)] {
+        match self {
+            Self::Start => &[(523, 100), (659, 100), (784, 150)],
+            Self::Next => &[(880, 80), (0, 80), (880, 80)],
+            Self::Captured => &[(1047, 60)],
+            Self::Done => &[(784, 100), (1047, 250)],
+            Self::Failed => &[(392, 200), (0, 100), (262, 400)],
+        }
+    }
+}
+
+///
+/// # Request a Cue
+///
+pub fn cue(cue: Cue) {
+    CUE.signal(cue);
+}
+
+#[embassy_executor::task]
+pub async fn task(p: BuzzerSrc) -> ! {
+    let buzz_pin = PwmPin::new(p.buzz_pin, Mode);
+    let beep_g = SimplePwm::new(
+        p.tim_p,
+        None,
+        Some(buzz_pin),
+        None,
+        None,
+        hz(1),
+        EdgeAlignedUp,
+    );
+
+    let mut buzzer: _ = Buzzer::new(beep_g, Channel::Ch2);
+    utils::panic::on_panic(silence);
+
+    let mut events = match Device::subscribe() {
+        None => panic!("{}: No Event Subscriber Left!!!", file!()),
+        Some(x) => x,
+    };
+
+    loop {
+        let cue = match select(CUE.wait(), events.next_message_pure()).await {
+            Either::First(cue) => cue,
+            Either::Second(event) => match alarm(&event) {
+                Some(cue) => cue,
+                None => continue,
+            },
+        };
+
+        buzzer.play(cue.tone()).await;
+    }
+}
+
+///
+/// # Silence on Panic
+///
+/// Safe-state hook, drives TIM12 CH2 low behind the driver's back.
+///
+fn silence() {
+    crate::hal::pac::TIM12.ccr(1).write(|w: _| w.set_ccr(0));
+}
+
+///
+/// # Alarm Cue of a Device Event
+///
+fn alarm(event: &Event<Device>) -> Option<Cue> {
+    match (event.device.criticality(), event.transition) {
+        (Criticality::Informational, _) => None,
+        (_, Transition::Offline) => Some(Cue::Failed),
+        (_, Transition::Recovered) => Some(Cue::Done),
+        (_, Transition::Online) => None,
+    }
+}
@@ -0,0 +1,233 @@
+//!
+//! # Console Task
+//!
+//! Line commands on USART1 at 115200 8N1, each answered with `ok` or
+//! `error: <reason>`. A line ends with CR or LF, words are separated
+//! by spaces.
+//!
+//! ## Commands
+//! - `calib acc`: Six-position accel calibration, offset and scale.
+//! - `calib acc cross`: Also the cross-axis terms.
+//! - `mount <preset>`: Preset [`Rotation`] by its name in lower case,
+//!   e.g. `mount roll180yaw90`.
+//! - `mount quat <w> <x> <y> <z>`: Any rotation, normalized.
+//! - `trim`: Measure the level trim, the robot resting level.
+//! - `trim clear`: Clear the level trim.
+//! - `filter <kind>`: Switch to `mahony`, `madgwick` or `eskf` with
+//!   its default gains.
+//! - `tune mahony <kp> <ki>`, `tune madgwick <beta>`,
+//!   `tune eskf <gyro> <bias> <acc>`: Filter gains, see [`Gains`].
+//! - `yaw zero`: The current heading becomes zero.
+//! - `order <order>`: Rotation order of the Euler angles, e.g. `zyx`.
+//! - `imu`: Log the latest IMU sample.
+//!
+//! Calibration and mounting changes are refused while armed or
+//! degraded, or whenever the system cannot start calibrating.
+//!
+
+use crate::tasks::bmi088::{self, Orientation, Rotation};
+use crate::{hal, system::*};
+use attitude::{EskfGains, Gains, Kind, MadgwickGains, MahonyGains, RotationOrder};
+use hal::{mode::Async, usart};
+use nalgebra::{Quaternion, UnitQuaternion};
+use usart::{Config, Uart};
+use utils::StaticCell;
+
+/// Longest Command Line in Bytes
+const LINE_MAX: usize = 64;
+/// Words per Command Line
+const WORDS_MAX: usize = 6;
+
+#[unsafe(link_section = ".axisram.console")]
+static BUFFER: StaticCell<[u8; LINE_MAX]> = StaticCell::new();
+
+#[embassy_executor::task]
+pub async fn task(p: Uart1Src) -> ! {
+    let buffer = BUFFER.init([0; _]);
+    let uart = Uart::new(
+        p.usart_p,
+        p.usart_rx,
+        p.usart_tx,
+        Irqs,
+        p.dma_tx,
+        p.dma_rx,
+        Config::default(),
+    );
+    let Ok(mut uart) = uart else {
+        panic!("{}: USART1 Config Rejected!!!", file!());
+    };
+
+    let mut line = [0; LINE_MAX];
+    let mut len = 0;
+    let mut overflow = false;
+
+    loop {
+        let n = match uart.read_until_idle(buffer).await {
+            Ok(n) => n,
+            Err(e) => {
+                defmt::warn!("Console: Read Failed: {:?}", e);
+                continue;
+            }
+        };
+
+        for &b in &buffer[..n] {
+            if b != b'\r' && b != b'\n' {
+                match len < LINE_MAX {
+                    true => line[len] = b,
+                    false => overflow = true,
+                }
+                len += 1;
+                continue;
+            }
+
+            let res = match (overflow, core::str::from_utf8(&line[..len.min(LINE_MAX)])) {
+                (true, _) => Some(Err("Line Too Long")),
+                (false, Ok(text)) => execute(text),
+                (false, Err(_)) => Some(Err("Not Text")),
+            };
+            (len, overflow) = (0, false);
+
+            if let Some(res) = res {
+                reply(&mut uart, res).await;
+            }
+        }
+    }
+}
+
+///
+/// # Execute a Command Line
+///
+/// `None` for a blank line, which is not answered.
+///
+fn execute(text: &str) -> Option<Result<(), &'static str>> {
+    let mut words = [""; WORDS_MAX];
+    let n = text
+        .split_ascii_whitespace()
+        .zip(&mut words)
+        .map(|(s, w): (&str, &mut &str)| *w = s)
+        .count();
+
+    if n == 0 {
+        return None;
+    }
+
+    defmt::debug!("Console: {=str}", text);
+    Some(run(&words[..n]))
+}
+
+fn run(words: &[&str]) -> Result<(), &'static str> {
+    match *words {
+        ["calib", "acc"] => refused_armed().map(|_: _| bmi088::calibrate_acc(false)),
+        ["calib", "acc", "cross"] => refused_armed().map(|_: _| bmi088::calibrate_acc(true)),
+        ["mount", "quat", w, x, y, z] => {
+            let quat = quat([w, x, y, z]).ok_or("Bad Quaternion")?;
+            refused_armed().map(|_: _| bmi088::set_mounting(Orientation::Custom(quat)))
+        }
+        ["mount", name] => {
+            let rotation = Rotation::from_name(name).ok_or("Unknown Preset")?;
+            refused_armed().map(|_: _| bmi088::set_mounting(Orientation::Preset(rotation)))
+        }
+        ["trim"] => refused_armed().map(|_: _| bmi088::level_trim()),
+        ["trim", "clear"] => refused_armed().map(|_: _| bmi088::clear_trim()),
+        ["filter", name] => kind(name).map(bmi088::select).ok_or("Unknown Filter"),
+        ["tune", ref gains @ ..] => parse_gains(gains).map(bmi088::tune),
+        ["yaw", "zero"] => {
+            bmi088::zero_yaw();
+            Ok(())
+        }
+        ["order", name] => order(name).map(bmi088::set_order).ok_or("Unknown Order"),
+        ["imu"] => {
+            let data = bmi088::latest().ok_or("No IMU Data Yet")?;
+            defmt::info!("Console: {:?}", data);
+            Ok(())
+        }
+        _ => Err("Unknown Command"),
+    }
+}
+
+/// Filter by Name
+fn kind(name: &str) -> Option<Kind> {
+    match name {
+        "mahony" => Some(Kind::Mahony),
+        "madgwick" => Some(Kind::Madgwick),
+        "eskf" => Some(Kind::Eskf),
+        _ => None,
+    }
+}
+
+/// Rotation Order by Name
+fn order(name: &str) -> Option<RotationOrder> {
+    match name {
+        "zyx" => Some(RotationOrder::Zyx),
+        "zxy" => Some(RotationOrder::Zxy),
+        "yxz" => Some(RotationOrder::Yxz),
+        "yzx" => Some(RotationOrder::Yzx),
+        "xyz" => Some(RotationOrder::Xyz),
+        "xzy" => Some(RotationOrder::Xzy),
+        _ => None,
+    }
+}
+
+/// Filter Name then its Gains
+fn parse_gains(words: &[&str]) -> Result<Gains, &'static str> {
+    let gains = match *words {
+        ["mahony", kp, ki] => Gains::Mahony(MahonyGains {
+            kp: num(kp)?,
+            ki: num(ki)?,
+        }),
+        ["madgwick", beta] => Gains::Madgwick(MadgwickGains { beta: num(beta)? }),
+        ["eskf", gyro, bias, acc] => Gains::Eskf(EskfGains {
+            gyro_noise: num(gyro)?,
+            bias_noise: num(bias)?,
+            acc_noise: num(acc)?,
+        }),
+        _ => return Err("Unknown Gains"),
+    };
+    Ok(gains)
+}
+
+/// Non-Negative Gain
+fn num(word: &str) -> Result<f64, &'static str> {
+    let x = word.parse::<f64>().map_err(|_: _| "Bad Number")?;
+    match x.is_finite() && x >= 0. {
+        true => Ok(x),
+        false => Err("Bad Number"),
+    }
+}
+
+/// Normalized `[w, x, y, z]`, `None` if not numbers or near zero
+fn quat(words: [&str; 4]) -> Option<UnitQuaternion<f64>> {
+    let mut c = [0.; 4];
+    for (c, w) in c.iter_mut().zip(words) {
+        *c = w.parse::<f64>().ok().filter(|x: &f64| x.is_finite())?;
+    }
+    UnitQuaternion::try_new(Quaternion::new(c[0], c[1], c[2], c[3]), 1e-6)
+}
+
+/// Checked before the Request, the IMU Task would Drop it
+fn refused_armed() -> Result<(), &'static str> {
+    match SysMode::get() {
+        SysMode::Armed => Err("Refused while Armed"),
+        SysMode::Degraded => Err("Refused while Degraded"),
+        SysMode::Calibrating => Err("Busy Calibrating"),
+        _ if !SysMode::Calibrating.can_enter() => Err("Refused in the Current Mode"),
+        _ => Ok(()),
+    }
+}
+
+async fn reply(uart: &mut Uart<'static, Async>, res: Result<(), &'static str>) {
+    let sent = match res {
+        Ok(()) => uart.write(b"ok\r\n").await,
+        Err(reason) => {
+            let mut sent = uart.write(b"error: ").await;
+            for part in [reason.as_bytes(), b"\r\n"] {
+                sent = sent.and(uart.write(part).await);
+            }
+            sent
+        }
+    };
+
+    if let Err(e) = sent {
+        defmt::warn!("Console: Write Failed: {:?}", e);
+    }
+}
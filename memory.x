@@ -5,7 +5,8 @@
 MEMORY
 {
     /* STM32H723xG             */
-    FLASH : ORIGIN = 0x08000000, LENGTH = 1M
+    /* the last 128K sector is reserved for `utils::storage` */
+    FLASH : ORIGIN = 0x08000000, LENGTH = 1M - 128K
 
     /* Instruction TCM (D1) */
     /* can be modified via the TCM_AXI_SHARED[1,0] register */
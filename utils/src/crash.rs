@@ -0,0 +1,393 @@
+//!
+//! # Crash and Reset Log
+//!
+//! Panics and HardFaults are written to backup SRAM with a checksum,
+//! along with the uptime and the [`SysMode`] at the time. On the next
+//! boot [`init`] classifies the reset from the RCC flags, clears them,
+//! and reports the previous crash record if there is one.
+//!
+//! ## Get the Reset Reason and the Previous Crash
+//! ```rust
+//! let reason: ResetReason = crash::reset_reason();
+//! let record: Option<CrashRecord> = crash::previous();
+//! ```
+//!
+
+use crate::atomic::{AtomicBool, Ordering::Relaxed as Order};
+use crate::prelude::sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use crate::prelude::{hal::pac, ll, time::Instant};
+use crate::status::SysMode;
+use core::cell::Cell;
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+use core::ptr::{read_volatile, write_volatile};
+use cortex_m_rt::{ExceptionFrame, exception};
+
+const MAGIC: u32 = u32::from_le_bytes(*b"CRSH");
+
+/// File Path Bytes Kept, the End of the Path
+const FILE_MAX: usize = 48;
+/// Message Bytes Kept
+const MSG_MAX: usize = 128;
+
+#[unsafe(link_section = ".bsram.crash")]
+static mut RECORD: CrashRecord = CrashRecord::EMPTY;
+
+/// A Crash is Being Recorded
+static CRASHED: AtomicBool = AtomicBool::new(false);
+
+static RESET: Mutex<RM, Cell<(ResetReason, Option<CrashRecord>)>> =
+    Mutex::new(Cell::new((ResetReason::Unknown, None)));
+
+///
+/// # Reset Reason
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum ResetReason {
+    PowerOn,
+    BrownOut,
+    /// NRST Pin, e.g. the Reset Button or the Debugger
+    Pin,
+    /// Independent Watchdog
+    Watchdog,
+    /// Window Watchdog
+    WindowWatchdog,
+    /// `SCB::sys_reset`
+    Software,
+    /// Illegal Low-Power Mode Entry
+    LowPower,
+    Unknown,
+}
+
+///
+/// # Crash Kind
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum CrashKind {
+    Panic,
+    HardFault,
+    /// Unreadable Kind
+    Unknown,
+}
+
+///
+/// # Crash Record
+///
+/// Kept in backup SRAM, any content is a valid value, and the
+/// checksum tells a record apart from leftover data.
+///
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq)]
+pub struct CrashRecord {
+    magic: u32,
+    kind: u32,
+    /// Uptime in ms
+    pub uptime_ms: u32,
+    mode: i32,
+    /// Panic Line, Zero for a HardFault
+    pub line: u32,
+
+    /// Stacked Program Counter
+    pub pc: u32,
+    /// Stacked Link Register
+    pub lr: u32,
+    /// Stacked Program Status
+    pub xpsr: u32,
+    /// Configurable Fault Status
+    pub cfsr: u32,
+    /// HardFault Status
+    pub hfsr: u32,
+    /// MemManage Fault Address
+    pub mmfar: u32,
+    /// BusFault Address
+    pub bfar: u32,
+
+    file: [u8; FILE_MAX],
+    msg: [u8; MSG_MAX],
+    crc: u32,
+}
+
+impl CrashRecord {
+    const EMPTY: Self = Self {
+        magic: 0,
+        kind: 0,
+        uptime_ms: 0,
+        mode: 0,
+        line: 0,
+        pc: 0,
+        lr: 0,
+        xpsr: 0,
+        cfsr: 0,
+        hfsr: 0,
+        mmfar: 0,
+        bfar: 0,
+        file: [0; _],
+        msg: [0; _],
+        crc: 0,
+    };
+
+    fn new(kind: CrashKind) -> Self {
+        Self {
+            magic: MAGIC,
+            kind: kind as u32,
+            uptime_ms: Instant::now().as_millis() as u32,
+            mode: SysMode::get().into_bits() as i32,
+            ..Self::EMPTY
+        }
+    }
+
+    ///
+    /// # Crash Kind
+    ///
+    pub fn kind(&self) -> CrashKind {
+        match self.kind {
+            0 => CrashKind::Panic,
+            1 => CrashKind::HardFault,
+            _ => CrashKind::Unknown,
+        }
+    }
+
+    ///
+    /// # System Mode at the Crash
+    ///
+    pub fn mode(&self) -> SysMode {
+        SysMode::from_bits(self.mode as i8)
+    }
+
+    ///
+    /// # Panic File, Empty for a HardFault
+    ///
+    pub fn file(&self) -> &str {
+        text(&self.file)
+    }
+
+    ///
+    /// # Panic Message, Empty for a HardFault
+    ///
+    pub fn message(&self) -> &str {
+        text(&self.msg)
+    }
+
+    fn checksum(&self) -> u32 {
+        let len = size_of::<Self>() - size_of::<u32>();
+        // Safety: `repr(C)` Integers and Bytes without Padding
+        let bytes = unsafe { core::slice::from_raw_parts((self as *const Self).cast::<u8>(), len) };
+        crate::crc::crc32(bytes)
+    }
+
+    fn seal(mut self) -> Self {
+        self.crc = self.checksum();
+        self
+    }
+
+    fn valid(&self) -> bool {
+        self.magic == MAGIC && self.crc == self.checksum()
+    }
+}
+
+impl defmt::Format for CrashRecord {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self.kind() {
+            CrashKind::HardFault => defmt::write!(
+                fmt,
+                "HardFault at {}ms in {:?}: PC={=u32:#010x} LR={=u32:#010x} xPSR={=u32:#010x} \
+                 CFSR={=u32:#010x} HFSR={=u32:#010x} MMFAR={=u32:#010x} BFAR={=u32:#010x}",
+                self.uptime_ms,
+                self.mode(),
+                self.pc,
+                self.lr,
+                self.xpsr,
+                self.cfsr,
+                self.hfsr,
+                self.mmfar,
+                self.bfar,
+            ),
+            _ => defmt::write!(
+                fmt,
+                "Panic at {}ms in {:?}: {=str} at {=str}:{}",
+                self.uptime_ms,
+                self.mode(),
+                self.message(),
+                self.file(),
+                self.line,
+            ),
+        }
+    }
+}
+
+///
+/// # Truncating Text Buffer
+///
+struct Text<'t> {
+    buf: &'t mut [u8],
+    len: usize,
+}
+
+impl Write for Text<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let n = s.len().min(self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+fn text(bytes: &[u8]) -> &str {
+    let len = bytes.iter().position(|&b: _| b == 0).unwrap_or(bytes.len());
+    match core::str::from_utf8(&bytes[..len]) {
+        Ok(s) => s,
+        // Cut inside a Character
+        Err(e) => core::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or(""),
+    }
+}
+
+fn store(record: Option<CrashRecord>) {
+    let record = match record {
+        Some(r) => r.seal(),
+        None => CrashRecord::EMPTY,
+    };
+    // Safety: Written Once per Crash, or at Boot before any Task
+    unsafe { write_volatile(&raw mut RECORD, record) };
+}
+
+///
+/// # Classify and Clear the Reset Flags
+///
+fn classify() -> ResetReason {
+    let rsr = pac::RCC.rsr().read();
+    pac::RCC.rsr().modify(|w: _| w.set_rmvf(true));
+
+    // Power-On also Sets the Brown-Out and Pin Flags, every Reset Drives the Pin
+    let flags = [
+        (rsr.porrstf(), ResetReason::PowerOn),
+        (rsr.borrstf(), ResetReason::BrownOut),
+        (rsr.iwdg1rstf(), ResetReason::Watchdog),
+        (rsr.wwdg1rstf(), ResetReason::WindowWatchdog),
+        (rsr.sftrstf(), ResetReason::Software),
+        (rsr.lpwrrstf(), ResetReason::LowPower),
+        (rsr.pinrstf(), ResetReason::Pin),
+    ];
+
+    flags
+        .into_iter()
+        .find_map(|(set, reason): _| set.then_some(reason))
+        .unwrap_or(ResetReason::Unknown)
+}
+
+///
+/// # Initialize the Crash Log
+///
+/// Called once by [`crate::sys_init`] with backup SRAM accessible.
+///
+pub(crate) fn init() {
+    let reason = classify();
+
+    // Safety: At Boot before any Task, Any Content is Valid
+    let record = unsafe { read_volatile(&raw const RECORD) };
+    let record = record.valid().then_some(record);
+    store(None);
+
+    RESET.lock(|r: _| r.set((reason, record)));
+
+    defmt::info!("Reset Reason: {:?}", reason);
+    if let Some(record) = record {
+        defmt::error!("Previous Crash: {:?}", record);
+    }
+}
+
+///
+/// # Get Reset Reason
+///
+pub fn reset_reason() -> ResetReason {
+    RESET.lock(|r: _| r.get().0)
+}
+
+///
+/// # Get Previous Crash
+///
+/// The crash that caused the last reset, `None` if there was none.
+///
+pub fn previous() -> Option<CrashRecord> {
+    RESET.lock(|r: _| r.get().1)
+}
+
+///
+/// # Record a Panic
+///
+/// Returns `false` if a crash is already being recorded.
+/// `None` for a defmt panic, whose message is only logged.
+///
+pub(crate) fn panicked(info: Option<&PanicInfo>) -> bool {
+    if CRASHED.swap(true, Order) {
+        return false;
+    }
+
+    let mut record = CrashRecord::new(CrashKind::Panic);
+
+    let mut msg = Text {
+        buf: &mut record.msg,
+        len: 0,
+    };
+    let _ = match info {
+        Some(info) => write!(msg, "{}", info.message()),
+        None => write!(msg, "defmt"),
+    };
+
+    if let Some(loc) = info.and_then(PanicInfo::location) {
+        let file = loc.file().as_bytes();
+        let file = &file[file.len().saturating_sub(FILE_MAX)..];
+        record.file[..file.len()].copy_from_slice(file);
+        record.line = loc.line();
+    }
+
+    store(Some(record));
+    true
+}
+
+///
+/// # Halt
+///
+/// Triggers a HardFault with `udf`, for the debugger to catch.
+/// The HardFault handler then applies the [`crate::panic`] policy.
+///
+pub fn halt() -> ! {
+    // `udf` would Raise a UsageFault instead if Enabled
+    const SHCSR: *mut u32 = 0xE000_ED24usize as _;
+    const USGFAULTENA: u32 = 1 << 18;
+
+    // Safety: Clearing an Enable Bit of the System Handler Control
+    unsafe { write_volatile(SHCSR, read_volatile(SHCSR) & !USGFAULTENA) };
+
+    ll::asm::udf()
+}
+
+#[exception]
+unsafe fn HardFault(ef: &ExceptionFrame) -> ! {
+    // A Panic Halting Here is Already Recorded
+    if !CRASHED.swap(true, Order) {
+        // Safety: Read-Only Fault Status Registers
+        let scb = unsafe { &*ll::peripheral::SCB::PTR };
+
+        store(Some(CrashRecord {
+            pc: ef.pc(),
+            lr: ef.lr(),
+            xpsr: ef.xpsr(),
+            cfsr: scb.cfsr.read(),
+            hfsr: scb.hfsr.read(),
+            mmfar: scb.mmfar.read(),
+            bfar: scb.bfar.read(),
+            ..CrashRecord::new(CrashKind::HardFault)
+        }));
+
+        defmt::error!("HardFault: PC={=u32:#010x}", ef.pc());
+    }
+
+    crate::panic::apply(spin)
+}
+
+/// Stay in the HardFault Handler
+fn spin() -> ! {
+    loop {
+        ll::asm::nop();
+    }
+}
@@ -0,0 +1,45 @@
+//!
+//! # CRC-32
+//!
+//! IEEE 802.3 polynomial, reflected, as used by zlib and Ethernet.
+//!
+
+/// Reflected Polynomial
+const POLY: u32 = 0xEDB8_8320;
+
+/// Lookup Table, Built at Compile Time
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = match c & 1 {
+                1 => POLY ^ (c >> 1),
+                _ => c >> 1,
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+///
+/// # CRC-32 Checksum
+///
+/// `crc32(b"123456789") == 0xCBF4_3926`
+///
+pub const fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < data.len() {
+        crc = TABLE[((crc ^ data[i] as u32) & 0xFF) as usize] ^ (crc >> 8);
+        i += 1;
+    }
+    !crc
+}
+
+const _: () = assert!(crc32(b"123456789") == 0xCBF4_3926);
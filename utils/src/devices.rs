@@ -0,0 +1,684 @@
+//!
+//! # Device Registry
+//!
+//! Heartbeat monitoring of devices declared once with [`devices!`],
+//! which generates the `Device` enum, its heartbeat table indexed by
+//! the discriminant and the `WATCH_LIST`.
+//!
+//! A device either has a heartbeat or does not exist, so a missing
+//! table entry is a compile error instead of a runtime panic.
+//!
+//! Each device has its own expiry time and a [`Criticality`], which
+//! grades the system [`Health`] when the device is lost. A feed sets
+//! the deadline of the device, checked on every health tick, so the
+//! expiry times are independent of each other and of the interval.
+//!
+//! Every state change of a heartbeat is published as an [`Event`] on
+//! the `EVENTS` channel of the registry.
+//!
+//! Each heartbeat also keeps [`DeviceStats`], printed as a table by
+//! the health task on request.
+//!
+//! ## Print the Statistics Table
+//! ```rust
+//! devices::request_stats();
+//! ```
+//!
+//! ## React to Device Events
+//! ```rust
+//! let mut events = Device::subscribe().unwrap();
+//!
+//! loop {
+//!     let event: Event<Device> = events.next_message_pure().await;
+//! }
+//! ```
+//!
+//! [`devices!`]: crate::devices
+//!
+
+use crate::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering::Relaxed as Order};
+use crate::prelude::sync::{blocking_mutex, signal::Signal};
+use crate::prelude::time::{Duration, Instant, Ticker};
+use bitfield_struct::bitenum;
+use blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use core::cell::RefCell;
+
+/// Events Queued per Subscriber, the Oldest are Dropped when Full
+pub const EVENT_CAP: usize = 8;
+/// Maximum Event Subscribers
+pub const EVENT_SUBS: usize = 4;
+
+/// Feed Rate Measurement Window
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+static HEALTH: AtomicU8 = AtomicU8::new(Health::Nominal.into_bits());
+static STATS: Signal<RM, ()> = Signal::new();
+
+///
+/// # Request the Statistics Table
+///
+pub fn request_stats() {
+    STATS.signal(());
+}
+
+///
+/// # Statistics Table Requested
+///
+pub fn stats_requested() -> bool {
+    STATS.try_take().is_some()
+}
+
+///
+/// # Device Criticality
+///
+/// What losing the device means for the system.
+///
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, defmt::Format)]
+pub enum Criticality {
+    /// Reported Only, e.g. Telemetry
+    Informational,
+    /// The System can Run with Reduced Function
+    Degraded,
+    /// The System cannot Run Safely
+    Critical,
+}
+
+impl Criticality {
+    ///
+    /// # System Health when Lost
+    ///
+    pub const fn health(self) -> Health {
+        match self {
+            Self::Informational => Health::Nominal,
+            Self::Degraded => Health::Degraded,
+            Self::Critical => Health::Critical,
+        }
+    }
+}
+
+///
+/// # Graded System Health
+///
+/// The worst effect of the devices lost after being online,
+/// graded by their [`Criticality`].
+///
+/// ## Get Current Health
+/// ```rust
+/// let health: Health = Health::get();
+/// ```
+///
+#[repr(u8)]
+#[bitenum]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, defmt::Format, Debug)]
+pub enum Health {
+    #[fallback]
+    Nominal = 0,
+    Degraded = 1,
+    Critical = 2,
+}
+
+impl Health {
+    ///
+    /// # Get System Health
+    ///
+    #[inline]
+    pub fn get() -> Health {
+        Health::from_bits(HEALTH.load(Order))
+    }
+
+    ///
+    /// # Set System Health
+    ///
+    /// Only the health task should grade the system.
+    ///
+    #[inline]
+    pub fn set(self) {
+        HEALTH.store(self.into_bits(), Order);
+    }
+}
+
+///
+/// # Heartbeat Transition
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum Transition {
+    /// First Feed after Boot
+    Online,
+    /// Expired or Killed while Online
+    Offline,
+    /// Fed again after Going Offline
+    Recovered,
+}
+
+///
+/// # Device Event
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub struct Event<D> {
+    pub device: D,
+    pub transition: Transition,
+    pub time: Instant,
+}
+
+///
+/// # Device Statistics
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct DeviceStats {
+    /// Total Online Time
+    pub uptime: Duration,
+    /// Current Online Time, Zero while Offline
+    pub online_for: Duration,
+    /// Times Gone Offline
+    pub dropouts: u32,
+    /// Longest Outage, Including the Current One
+    pub longest_outage: Duration,
+    /// Last Feed, `None` if Never Fed
+    pub last_seen: Option<Instant>,
+    /// Measured Feed Rate in Hz
+    pub feed_hz: f32,
+}
+
+///
+/// # Heartbeat Record
+///
+struct Record {
+    /// Start of the Current Online Period
+    since: Instant,
+    last_seen: Option<Instant>,
+    /// Completed Online Periods
+    uptime: Duration,
+    dropouts: u32,
+    longest: Duration,
+    feeds: u32,
+    window: Instant,
+    rate: f32,
+}
+
+///
+/// # Heartbeat Structure
+///
+pub struct HeartBeat {
+    online: AtomicBool,
+    seen: AtomicBool,
+    /// Expiry Deadline in Ticks
+    deadline: AtomicU64,
+    record: Mutex<RM, RefCell<Record>>,
+}
+
+impl Default for HeartBeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeartBeat {
+    pub const fn new() -> Self {
+        Self {
+            online: AtomicBool::new(false),
+            seen: AtomicBool::new(false),
+            deadline: AtomicU64::new(0),
+            record: Mutex::new(RefCell::new(Record {
+                since: Instant::MIN,
+                last_seen: None,
+                uptime: Duration::MIN,
+                dropouts: 0,
+                longest: Duration::MIN,
+                feeds: 0,
+                window: Instant::MIN,
+                rate: 0.,
+            })),
+        }
+    }
+
+    fn record<R>(&self, f: impl FnOnce(&mut Record) -> R) -> R {
+        self.record.lock(|r: _| f(&mut r.borrow_mut()))
+    }
+
+    ///
+    ///  # Feed Heartbeat
+    ///
+    /// Set the device as online until `expire` from now.
+    ///
+    /// Returns the transition if the device was offline.
+    ///
+    pub fn feed(&self, expire: Duration) -> Option<Transition> {
+        let now = Instant::now();
+        self.deadline.store((now + expire).as_ticks(), Order);
+        let edge = match (self.online.swap(true, Order), self.seen.swap(true, Order)) {
+            (true, _) => None,
+            (false, false) => Some(Transition::Online),
+            (false, true) => Some(Transition::Recovered),
+        };
+
+        self.record(|r: _| {
+            if edge.is_some() {
+                let outage = r.last_seen.map(|t: _| now.saturating_duration_since(t));
+                r.longest = r.longest.max(outage.unwrap_or(Duration::MIN));
+                r.since = now;
+            }
+            r.last_seen = Some(now);
+            r.feeds = r.feeds.saturating_add(1);
+        });
+
+        edge
+    }
+
+    ///
+    /// # Kill Heartbeat
+    ///
+    /// Set the device as offline and clear its deadline.
+    ///
+    /// Returns the transition if the device was online.
+    ///
+    pub fn kill(&self) -> Option<Transition> {
+        self.deadline.store(0, Order);
+        if !self.online.swap(false, Order) {
+            return None;
+        }
+
+        self.record(|r: _| {
+            let end = r.last_seen.unwrap_or(r.since);
+            r.uptime += end.saturating_duration_since(r.since);
+            r.dropouts = r.dropouts.saturating_add(1);
+        });
+
+        Some(Transition::Offline)
+    }
+
+    ///
+    /// # Check Online Status
+    ///
+    /// Returns `true` if the device is online, `false` otherwise.
+    ///
+    pub fn check(&self) -> bool {
+        self.online.load(Order)
+    }
+
+    ///
+    /// # Check Lost Status
+    ///
+    /// Returns `true` if the device is offline after being online.
+    ///
+    pub fn lost(&self) -> bool {
+        self.seen.load(Order) && !self.online.load(Order)
+    }
+
+    ///
+    /// # Get Time Left
+    ///
+    /// Returns the time until the deadline, zero once expired.
+    ///
+    pub fn ttl(&self) -> Duration {
+        let deadline = Instant::from_ticks(self.deadline.load(Order));
+        deadline.saturating_duration_since(Instant::now())
+    }
+
+    ///
+    /// # Tick Heartbeat
+    ///
+    /// Mark the device as offline once its deadline has passed.
+    ///
+    /// Returns the transition if the device has just expired.
+    ///
+    pub fn tick(&self) -> Option<Transition> {
+        let now = Instant::now();
+        self.record(|r: _| {
+            let dt = now.saturating_duration_since(r.window);
+            if dt >= RATE_WINDOW {
+                r.rate = r.feeds as f32 * 1e6 / dt.as_micros() as f32;
+                (r.feeds, r.window) = (0, now);
+            }
+        });
+
+        if now.as_ticks() >= self.deadline.load(Order) {
+            return self.kill(); // Offline
+        }
+
+        None // Still Online
+    }
+
+    ///
+    /// # Get Statistics
+    ///
+    pub fn stats(&self) -> DeviceStats {
+        let now = Instant::now();
+        let online = self.check();
+
+        self.record(|r: _| {
+            let online_for = match online {
+                true => now.saturating_duration_since(r.since),
+                false => Duration::MIN,
+            };
+            let outage = match (online, r.last_seen) {
+                (false, Some(t)) => now.saturating_duration_since(t),
+                _ => Duration::MIN,
+            };
+
+            DeviceStats {
+                uptime: r.uptime + online_for,
+                online_for,
+                dropouts: r.dropouts,
+                longest_outage: r.longest.max(outage),
+                last_seen: r.last_seen,
+                feed_hz: r.rate,
+            }
+        })
+    }
+
+    ///
+    /// # Wait for Online
+    ///
+    pub async fn wait(&self, t: &mut Ticker) {
+        while !self.check() {
+            t.next().await
+        }
+    }
+}
+
+///
+/// # Health Display
+///
+/// **impl [defmt::Format]**
+///
+pub struct Display {
+    pub name: &'static str,
+    pub heart: &'static HeartBeat,
+}
+
+impl defmt::Format for Display {
+    fn format(&self, fmt: defmt::Formatter) {
+        match self.heart.check() {
+            true => {
+                let ttl = self.heart.ttl().as_millis();
+                defmt::write!(fmt, "{} (Online, TTL={}ms)", self.name, ttl)
+            }
+            false => defmt::write!(fmt, "{} (Offline)", self.name),
+        }
+    }
+}
+
+///
+/// # devices
+///
+/// Declare the monitored devices of a firmware.
+///
+/// Generates `Device` with one variant per entry, its heartbeat table
+/// and `WATCH_LIST`. An expiry is noticed on the next health check, up
+/// to `interval_ms` late.
+///
+/// ## Example
+/// ```
+/// utils::devices! {
+///     interval_ms: 100;
+///
+///     /// Bosch BMI088 IMU
+///     Bmi088 => {
+///         name: "BMI088",
+///         expire_ms: 500,
+///         criticality: Critical,
+///     },
+/// }
+///
+/// Device::Bmi088.feed();
+/// ```
+///
+#[macro_export]
+macro_rules! devices {
+    (
+        interval_ms: $interval:expr;
+
+        $(
+            $(#[$meta:meta])*
+            $dev:ident => {
+                name: $name:expr,
+                expire_ms: $expire:expr,
+                criticality: $crit:ident $(,)?
+            }
+        ),* $(,)?
+    ) => {
+        ///
+        /// # Device Enumeration
+        ///
+        #[derive(Clone, Copy, PartialEq, Eq, Debug, ::defmt::Format)]
+        pub enum Device {
+            $( $(#[$meta])* $dev, )*
+        }
+
+        ///
+        /// # Watch List of Monitored Devices
+        ///
+        pub const WATCH_LIST: &[Device] = &Device::ALL;
+
+        ///
+        /// # Device Event Channel
+        ///
+        pub static EVENTS: $crate::prelude::sync::pubsub::PubSubChannel<
+            $crate::prelude::sync::blocking_mutex::raw::CriticalSectionRawMutex,
+            $crate::devices::Event<Device>,
+            { $crate::devices::EVENT_CAP },
+            { $crate::devices::EVENT_SUBS },
+            0,
+        > = $crate::prelude::sync::pubsub::PubSubChannel::new();
+
+        ///
+        /// # Device Event Subscriber
+        ///
+        pub type Events = $crate::prelude::sync::pubsub::Subscriber<
+            'static,
+            $crate::prelude::sync::blocking_mutex::raw::CriticalSectionRawMutex,
+            $crate::devices::Event<Device>,
+            { $crate::devices::EVENT_CAP },
+            { $crate::devices::EVENT_SUBS },
+            0,
+        >;
+
+        static HEARTS: [$crate::devices::HeartBeat; Device::COUNT] =
+            [const { $crate::devices::HeartBeat::new() }; Device::COUNT];
+
+        const _: () = {
+            let mut i = 0;
+            while i < Device::COUNT {
+                let dev = Device::ALL[i];
+                assert!(dev as usize == i);
+                assert!(dev.expire_ms() > 0);
+                i += 1;
+            }
+        };
+
+        /// Settings for Heartbeat Monitoring
+        impl Device {
+            /// Health Check Interval in ms
+            pub const HEALTH_MS: u8 = $interval;
+
+            /// Number of Devices
+            pub const COUNT: usize = <[Device]>::len(&[$(Device::$dev),*]);
+
+            /// All Devices in Declaration Order
+            pub const ALL: [Device; Device::COUNT] = [$(Device::$dev),*];
+
+            ///
+            /// # Display Name
+            ///
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $( Self::$dev => $name, )*
+                }
+            }
+
+            ///
+            /// # Expiry Time in ms
+            ///
+            pub const fn expire_ms(self) -> u32 {
+                match self {
+                    $( Self::$dev => $expire, )*
+                }
+            }
+
+            ///
+            /// # Criticality
+            ///
+            pub const fn criticality(self) -> $crate::devices::Criticality {
+                match self {
+                    $( Self::$dev => $crate::devices::Criticality::$crit, )*
+                }
+            }
+
+            ///
+            /// # Expiry Time
+            ///
+            pub const fn expire(self) -> $crate::prelude::time::Duration {
+                $crate::prelude::time::Duration::from_millis(self.expire_ms() as u64)
+            }
+
+            ///
+            /// # Get Health Check Interval
+            ///
+            /// Returns the health check interval in milliseconds.
+            ///
+            pub const fn interval() -> u64 {
+                Self::HEALTH_MS as _
+            }
+
+            ///
+            /// # Get Heartbeat
+            ///
+            pub fn heartbeat(self) -> &'static $crate::devices::HeartBeat {
+                &HEARTS[self as usize]
+            }
+
+            ///
+            /// # Subscribe to Device Events
+            ///
+            /// Returns `None` if all subscribers are taken.
+            ///
+            pub fn subscribe() -> Option<Events> {
+                EVENTS.subscriber().ok()
+            }
+
+            ///
+            /// # Publish Transition
+            ///
+            fn publish(self, transition: Option<$crate::devices::Transition>) {
+                if let Some(transition) = transition {
+                    let event = $crate::devices::Event {
+                        device: self,
+                        transition,
+                        time: $crate::prelude::time::Instant::now(),
+                    };
+                    EVENTS.immediate_publisher().publish_immediate(event);
+                }
+            }
+
+            ///
+            /// # Feed Heartbeat
+            ///
+            pub fn feed(self) {
+                self.publish(self.heartbeat().feed(self.expire()))
+            }
+
+            ///
+            /// # Kill Heartbeat
+            ///
+            pub fn kill(self) {
+                self.publish(self.heartbeat().kill())
+            }
+
+            ///
+            /// # Check Heartbeat
+            ///
+            /// Check if the heartbeat for this device is alive.
+            ///
+            pub fn check(self) -> bool {
+                self.heartbeat().check()
+            }
+
+            ///
+            /// # Check Lost
+            ///
+            /// Check if this device went offline after being online.
+            ///
+            pub fn lost(self) -> bool {
+                self.heartbeat().lost()
+            }
+
+            ///
+            /// # Grade System Health
+            ///
+            /// The worst health among the lost devices.
+            ///
+            pub fn health() -> $crate::devices::Health {
+                Self::ALL
+                    .iter()
+                    .filter(|d: &&Self| d.lost())
+                    .map(|d: _| d.criticality().health())
+                    .max()
+                    .unwrap_or($crate::devices::Health::Nominal)
+            }
+
+            ///
+            /// # Tick Heartbeat
+            ///
+            /// Expire the device if its deadline has passed.
+            ///
+            /// - `true` if the device is still online.
+            /// - `false` if the device has gone offline.
+            ///
+            pub fn tick(self) -> bool {
+                self.publish(self.heartbeat().tick());
+                self.check()
+            }
+
+            ///
+            /// # Wait for Device to be Online
+            ///
+            pub fn wait(
+                self,
+                t: &mut $crate::prelude::time::Ticker,
+            ) -> impl ::core::future::Future<Output = ()> + '_ {
+                self.heartbeat().wait(t)
+            }
+
+            ///
+            /// # Get Statistics
+            ///
+            pub fn stats(self) -> $crate::devices::DeviceStats {
+                self.heartbeat().stats()
+            }
+
+            ///
+            /// # Log Statistics Table
+            ///
+            pub fn log_stats() {
+                ::defmt::info!(
+                    "Device | Uptime ms | Online ms | Dropouts | Longest Outage ms | Last Seen | Feed Hz"
+                );
+                for dev in Self::ALL {
+                    let s = dev.stats();
+                    ::defmt::info!(
+                        "{=str} | {} | {} | {} | {} | {} | {}",
+                        dev.name(),
+                        s.uptime.as_millis(),
+                        s.online_for.as_millis(),
+                        s.dropouts,
+                        s.longest_outage.as_millis(),
+                        s.last_seen,
+                        s.feed_hz,
+                    );
+                }
+            }
+
+            ///
+            /// # Display Health
+            ///
+            /// **impl [defmt::Format]**
+            ///
+            pub fn display(self) -> $crate::devices::Display {
+                $crate::devices::Display {
+                    name: self.name(),
+                    heart: self.heartbeat(),
+                }
+            }
+        }
+    };
+}
@@ -4,7 +4,10 @@
 
 use crate::prelude::{hal, ll};
 use hal::{Config, Peripherals, init, rcc, time::mhz};
-use ll::{Peripherals as CorePeripherals, peripheral::SCB};
+use ll::{
+    Peripherals as CorePeripherals,
+    peripheral::{DWT, SCB},
+};
 
 // __pre_init function to be called before main
 core::arch::global_asm! {
@@ -40,6 +43,9 @@ core::arch::global_asm! {
     "bx lr", // Return from __pre_init
 }
 
+/// Core Clock in Hz, as Configured by [`sys_init`]
+pub const SYSCLK_HZ: u32 = 520_000_000;
+
 ///
 /// # System Initialization Function
 ///
@@ -48,6 +54,9 @@ core::arch::global_asm! {
 pub fn sys_init() -> (CorePeripherals, Peripherals) {
     defmt::debug!("System Initialization...");
 
+    // Before any Interrupt may Push a Frame
+    crate::profile::paint();
+
     let core = match CorePeripherals::take() {
         None => panic!("{}: Can Be Called Only Once!!!", file!()),
         Some(mut x) => {
@@ -55,6 +64,11 @@ pub fn sys_init() -> (CorePeripherals, Peripherals) {
             let i = SCB::icache_enabled();
             let d = SCB::dcache_enabled();
             defmt::trace!("icache: {}, dcache: {}", i, d);
+
+            // Cycle Counter, for `profile` and `probe`
+            x.DCB.enable_trace();
+            DWT::unlock();
+            x.DWT.enable_cycle_counter();
             x
         }
     };
@@ -124,5 +138,15 @@ pub fn sys_init() -> (CorePeripherals, Peripherals) {
         init(config) // SysClock = 520MHz
     };
 
+    // Backup SRAM Clock and Write Access, for Records Kept across Resets
+    hal::pac::RCC.ahb4enr().modify(|w: _| w.set_bkpsramen(true));
+    hal::pac::PWR.cr1().modify(|w: _| w.set_dbp(true));
+
+    // Freeze the Watchdog while Halted by the Debugger
+    hal::pac::DBGMCU.apb4fzr1().modify(|w: _| w.set_iwdg1(true));
+
+    crate::crash::init();
+    crate::panic::init();
+
     (core, peripherals)
 }
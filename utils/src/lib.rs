@@ -3,13 +3,22 @@
 #![allow(unused_imports)]
 
 use ::defmt_rtt as _;
-use ::panic_probe as _;
 
 mod init;
 mod macros;
 
+pub mod crash;
+pub mod crc;
+pub mod devices;
+pub mod panic;
+pub mod probe;
+pub mod profile;
+pub mod status;
+pub mod storage;
+pub mod watchdog;
+
 pub use bitfield_struct::*;
-pub use init::sys_init;
+pub use init::{SYSCLK_HZ, sys_init};
 pub use prelude::ll::asm;
 pub use prelude::ll::peripheral;
 pub use prelude::time::Timer as T;
@@ -29,8 +38,18 @@ pub mod prelude {
     pub use ::embassy_time as time; // Time
 }
 
+/// # Panic Handler
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    if crash::panicked(Some(info)) {
+        ::defmt::error!("{}", ::defmt::Display2Format(info));
+    }
+    panic::apply(crash::halt)
+}
+
 /// # Defmt Panic Handler
 #[::defmt::panic_handler]
 fn soft_panic() -> ! {
-    ::panic_probe::hard_fault()
+    crash::panicked(None);
+    panic::apply(crash::halt)
 }
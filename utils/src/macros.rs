@@ -58,3 +58,35 @@ macro_rules! init_ticker {
         Ticker::every(Duration::from_secs($val))
     }};
 }
+
+///
+/// # measure
+///
+/// Time a block or the rest of a scope with a [`Probe`], declared
+/// once per call site.
+///
+/// `measure!(name, expr)` evaluates `expr` and returns its value,
+/// `measure!(name)` returns a span recorded when dropped. Early
+/// returns, e.g. by `?`, are recorded as well.
+///
+/// ## Example
+/// ```
+/// let (x, y, z) = measure!("BMI088 Gyro", imu.read_gyro().await?);
+///
+/// let _m = measure!("Filter Update"); // Until the End of the Scope
+/// ```
+///
+/// [`Probe`]: crate::probe::Probe
+///
+#[macro_export]
+macro_rules! measure {
+    ($name:expr) => {{
+        static PROBE: $crate::probe::Probe = $crate::probe::Probe::new($name);
+        PROBE.start()
+    }};
+
+    ($name:expr, $body:expr) => {{
+        let _span = $crate::measure!($name);
+        $body
+    }};
+}
@@ -0,0 +1,188 @@
+//!
+//! # Panic Policy
+//!
+//! What happens once a panic or a HardFault is recorded by
+//! [`crate::crash`], selected by the `panic-halt`, `panic-reset` or
+//! `panic-safe` cargo feature of `utils`. Without any, debug builds
+//! halt for the debugger and release builds run the safe-state hooks,
+//! then reset.
+//!
+//! ## Register a Safe-State Hook
+//! ```rust
+//! utils::panic::on_panic(heat_off);
+//! ```
+//!
+//! ## Boot Loop
+//!
+//! Crash resets are counted in backup SRAM. [`BOOT_LOOP_CRASHES`]
+//! within [`BOOT_LOOP_MS`] of uptime boot into [`SysMode::Fault`], and
+//! [`boot_loop`] tells the application to start only what is needed to
+//! report it. Any reset without a crash, e.g. the reset button, clears
+//! the count.
+//!
+//! ```rust
+//! if utils::panic::boot_loop() {
+//!     // Spawn the Minimal Tasks only
+//! }
+//! ```
+//!
+
+use crate::atomic::{AtomicBool, Ordering::Relaxed as Order};
+use crate::crash;
+use crate::crc::crc32;
+use crate::prelude::sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use crate::prelude::{ll::peripheral::SCB, time::Instant};
+use crate::status::{Reason, SysMode};
+use core::cell::Cell;
+use core::ptr::{read_volatile, write_volatile};
+
+/// Policy, Selected by Cargo Feature
+pub const POLICY: Policy = match () {
+    _ if cfg!(feature = "panic-halt") => Policy::Halt,
+    _ if cfg!(feature = "panic-reset") => Policy::Reset,
+    _ if cfg!(feature = "panic-safe") => Policy::SafeReset,
+    _ if cfg!(debug_assertions) => Policy::Halt,
+    _ => Policy::SafeReset,
+};
+
+/// Crashes before a Boot Loop
+pub const BOOT_LOOP_CRASHES: u32 = 3;
+/// Total Uptime of the Crashed Runs in ms
+pub const BOOT_LOOP_MS: u32 = 30_000;
+
+/// Safe-State Hooks at Once
+const HOOKS: usize = 8;
+const MAGIC: u32 = u32::from_le_bytes(*b"LOOP");
+
+/// Crash Streak: Magic, Crashes, Uptime ms, CRC
+#[unsafe(link_section = ".bsram.panic")]
+static mut STREAK: [u32; 4] = [0; _];
+
+static HOOK_LIST: Mutex<RM, Cell<[Option<Hook>; HOOKS]>> = Mutex::new(Cell::new([None; _]));
+static BOOT_LOOP: AtomicBool = AtomicBool::new(false);
+/// The Policy is Being Applied
+static FAILING: AtomicBool = AtomicBool::new(false);
+
+/// Safe-State Hook
+pub type Hook = fn();
+
+///
+/// # Panic Policy
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum Policy {
+    /// Stop in Place for the Debugger
+    Halt,
+    /// Reset at Once
+    Reset,
+    /// Run the Safe-State Hooks, then Reset
+    SafeReset,
+}
+
+///
+/// # Register a Safe-State Hook
+///
+/// Called on a crash under [`Policy::SafeReset`], in registration
+/// order, from the panic or HardFault handler. Hooks must not block
+/// nor rely on any driver state, write the registers directly.
+///
+pub fn on_panic(hook: Hook) {
+    let full = HOOK_LIST.lock(|h: _| {
+        let mut hooks = h.get();
+        let slot = hooks.iter_mut().find(|x: &&mut _| x.is_none());
+        let full = slot.map(|x: _| *x = Some(hook)).is_none();
+        h.set(hooks);
+        full
+    });
+
+    if full {
+        panic!("{}: No Panic Hook Slot Left!!!", file!());
+    }
+}
+
+///
+/// # Booted in a Loop
+///
+/// `true` if the last resets were a crash loop, the system is then
+/// held in [`SysMode::Fault`].
+///
+pub fn boot_loop() -> bool {
+    BOOT_LOOP.load(Order)
+}
+
+fn load() -> (u32, u32) {
+    // Safety: At Boot or while Failing, Any Content is Valid
+    let w = unsafe { read_volatile(&raw const STREAK) };
+    match w[0] == MAGIC && w[3] == checksum(&w) {
+        true => (w[1], w[2]),
+        false => (0, 0),
+    }
+}
+
+fn store(crashes: u32, uptime_ms: u32) {
+    let mut w = [MAGIC, crashes, uptime_ms, 0];
+    w[3] = checksum(&w);
+    // Safety: At Boot or while Failing
+    unsafe { write_volatile(&raw mut STREAK, w) };
+}
+
+fn checksum(w: &[u32; 4]) -> u32 {
+    let mut bytes = [0; 12];
+    for (b, w) in bytes.as_chunks_mut::<4>().0.iter_mut().zip(&w[..3]) {
+        *b = w.to_le_bytes();
+    }
+    crc32(&bytes)
+}
+
+///
+/// # Initialize the Panic Policy
+///
+/// Called once by [`crate::sys_init`] after [`crash::init`].
+///
+pub(crate) fn init() {
+    defmt::debug!("Panic Policy: {:?}", POLICY);
+
+    if crash::previous().is_none() {
+        return store(0, 0);
+    }
+
+    let (crashes, uptime_ms) = load();
+    if crashes >= BOOT_LOOP_CRASHES {
+        defmt::error!(
+            "Boot Loop: {} Crashes within {}ms, Safe Mode!!!",
+            crashes,
+            uptime_ms
+        );
+        BOOT_LOOP.store(true, Order);
+        let _ = SysMode::Fault.enter(Reason::BootLoop);
+    }
+}
+
+///
+/// # Apply the Policy
+///
+/// Called by the panic and HardFault handlers once the crash is
+/// recorded, `halt` stops the core in place.
+///
+pub(crate) fn apply(halt: fn() -> !) -> ! {
+    if POLICY == Policy::Halt {
+        halt()
+    }
+
+    // A Crash in a Hook Resets at Once
+    if !FAILING.swap(true, Order) {
+        if POLICY == Policy::SafeReset {
+            let hooks = HOOK_LIST.lock(|h: _| h.get());
+            hooks.into_iter().flatten().for_each(|hook: Hook| hook());
+        }
+
+        let uptime_ms = Instant::now().as_millis() as u32;
+        let (crashes, total) = load();
+        match total.saturating_add(uptime_ms) <= BOOT_LOOP_MS {
+            true => store(crashes + 1, total + uptime_ms),
+            false => store(1, uptime_ms),
+        }
+    }
+
+    SCB::sys_reset()
+}
@@ -0,0 +1,218 @@
+//!
+//! # Timing Probes
+//!
+//! Named probes recording the DWT cycles spent in a block, with the
+//! minimum, average and maximum since boot or the last reset. Usually
+//! declared by [`measure!`], each probe lists itself on its first
+//! sample and the list is logged as a table.
+//!
+//! A block holding an `.await` is measured from start to end, the
+//! time other tasks run in between included. Spans longer than a
+//! cycle counter wrap, about 8s, are not meaningful.
+//!
+//! ## Measure a Block
+//! ```rust
+//! let (x, y, z) = measure!("BMI088 Gyro", imu.read_gyro().await?);
+//! ```
+//!
+//! ## Measure the Rest of a Scope
+//! ```rust
+//! let _m = measure!("Filter Update");
+//! ```
+//!
+//! ## Print the Table
+//! ```rust
+//! probe::log_table();
+//! ```
+//!
+//! [`measure!`]: crate::measure
+//!
+
+use crate::SYSCLK_HZ;
+use crate::atomic::{AtomicBool, Ordering::Relaxed as Order};
+use crate::prelude::ll::peripheral::DWT;
+use crate::prelude::sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use core::cell::{Cell, RefCell};
+
+/// Probes Listed at Once
+pub const PROBES: usize = 16;
+
+static LIST: Mutex<RM, RefCell<[Option<&'static Probe>; PROBES]>> =
+    Mutex::new(RefCell::new([None; _]));
+
+///
+/// # Get Cycle Count
+///
+/// The DWT cycle counter, enabled by [`crate::sys_init`].
+///
+#[inline]
+pub fn cycles() -> u32 {
+    DWT::cycle_count()
+}
+
+///
+/// # Cycles to µs
+///
+#[inline]
+pub fn to_us(cycles: u32) -> f32 {
+    cycles as f32 / (SYSCLK_HZ / 1_000_000) as f32
+}
+
+///
+/// # Probe Statistics
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct ProbeStats {
+    /// Samples Recorded
+    pub count: u32,
+    /// Minimum Cycles
+    pub min: u32,
+    /// Maximum Cycles
+    pub max: u32,
+    /// Total Cycles
+    pub total: u64,
+}
+
+impl ProbeStats {
+    const EMPTY: Self = Self {
+        count: 0,
+        min: u32::MAX,
+        max: 0,
+        total: 0,
+    };
+
+    ///
+    /// # Average Cycles
+    ///
+    pub fn avg(&self) -> u32 {
+        (self.total / (self.count as u64).max(1)) as u32
+    }
+}
+
+///
+/// # Timing Probe
+///
+pub struct Probe {
+    name: &'static str,
+    stats: Mutex<RM, Cell<ProbeStats>>,
+    listed: AtomicBool,
+}
+
+impl Probe {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            stats: Mutex::new(Cell::new(ProbeStats::EMPTY)),
+            listed: AtomicBool::new(false),
+        }
+    }
+
+    ///
+    /// # Probe Name
+    ///
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    ///
+    /// # Get Statistics
+    ///
+    pub fn stats(&self) -> ProbeStats {
+        self.stats.lock(|s: _| s.get())
+    }
+
+    ///
+    /// # Reset Statistics
+    ///
+    pub fn reset(&self) {
+        self.stats.lock(|s: _| s.set(ProbeStats::EMPTY));
+    }
+
+    ///
+    /// # Record a Sample
+    ///
+    /// Lists the probe on its first sample, unless the list is full.
+    ///
+    pub fn record(&'static self, cycles: u32) {
+        self.stats.lock(|s: _| {
+            let mut x = s.get();
+            x.count = x.count.wrapping_add(1);
+            x.min = x.min.min(cycles);
+            x.max = x.max.max(cycles);
+            x.total += cycles as u64;
+            s.set(x);
+        });
+
+        if !self.listed.swap(true, Order) {
+            LIST.lock(|l: _| {
+                let mut l = l.borrow_mut();
+                if let Some(slot) = l.iter_mut().find(|x: &&mut _| x.is_none()) {
+                    *slot = Some(self);
+                }
+            });
+        }
+    }
+
+    ///
+    /// # Start a Span
+    ///
+    /// Records the cycles until the returned span is dropped.
+    ///
+    pub fn start(&'static self) -> Span {
+        Span {
+            probe: self,
+            start: cycles(),
+        }
+    }
+}
+
+///
+/// # Measured Span
+///
+pub struct Span {
+    probe: &'static Probe,
+    start: u32,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        self.probe.record(cycles().wrapping_sub(self.start));
+    }
+}
+
+///
+/// # Get Listed Probes
+///
+/// In order of their first sample.
+///
+pub fn probes() -> [Option<&'static Probe>; PROBES] {
+    LIST.lock(|l: _| *l.borrow())
+}
+
+///
+/// # Reset All Probes
+///
+pub fn reset_all() {
+    probes().into_iter().flatten().for_each(Probe::reset);
+}
+
+///
+/// # Log the Probe Table
+///
+pub fn log_table() {
+    defmt::info!("Probe: Count, Min / Avg / Max Cycles (µs)");
+    for p in probes().into_iter().flatten() {
+        let s = p.stats();
+        defmt::info!(
+            "{=str}: {}, {} / {} / {} ({} / {} / {})",
+            p.name(),
+            s.count,
+            s.min,
+            s.avg(),
+            s.max,
+            to_us(s.min),
+            to_us(s.avg()),
+            to_us(s.max),
+        );
+    }
+}
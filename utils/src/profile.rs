@@ -0,0 +1,345 @@
+//!
+//! # Executor Profiling
+//!
+//! CPU load, per-task run time and the stack high-water mark.
+//!
+//! The executor trace hooks mark every poll: the cycles from the poll
+//! start to the idle hook, right before `WFE`, are busy, and each
+//! task is charged the cycles of its own polls, both counted by the
+//! DWT cycle counter. Interrupts are charged to whatever they
+//! interrupt, and not at all while the core sleeps.
+//!
+//! The stack is painted at boot, the high-water mark is the deepest
+//! word overwritten since.
+//!
+//! ## Name a Task
+//! ```rust
+//! s.must_spawn(profile::named("Health", tasks::health::task(r.watchdog)));
+//! ```
+//!
+//! ## Print the Report
+//! ```rust
+//! // Every `REPORT_MS`, or at once after a Request
+//! profile::request_report();
+//!
+//! if profile::report_due() {
+//!     profile::log_report();
+//! }
+//! ```
+//!
+
+use crate::SYSCLK_HZ;
+use crate::prelude::sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use crate::prelude::sync::signal::Signal;
+use crate::prelude::time::{Duration, Instant};
+use crate::prelude::{ll, ll::peripheral::DWT};
+use core::cell::RefCell;
+use core::ptr::{read_volatile, write_volatile};
+use embassy_executor::SpawnToken;
+
+/// Tasks Tracked at Once
+pub const TASKS: usize = 16;
+/// Report Period in ms
+pub const REPORT_MS: u64 = 10_000;
+
+/// Unused Stack Word
+const PAINT: u32 = 0xA5A5_A5A5;
+/// Stack Left Unpainted below the Painter
+const MARGIN: usize = 64;
+
+static STATE: Mutex<RM, RefCell<State>> = Mutex::new(RefCell::new(State {
+    poll: 0,
+    exec: 0,
+    busy: 0,
+    tasks: [None; TASKS],
+    last: Snapshot {
+        time: Instant::MIN,
+        busy: 0,
+        tasks: [None; TASKS],
+    },
+}));
+static REPORT: Signal<RM, ()> = Signal::new();
+
+unsafe extern "C" {
+    /// Stack Top, Highest Address
+    static _stack_start: u32;
+    /// Stack Limit, Lowest Address
+    static _stack_end: u32;
+}
+
+///
+/// # Task Run-Time
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct TaskStats {
+    /// Task Id, the Address of its Header
+    pub id: u32,
+    pub name: Option<&'static str>,
+    /// Polls since Boot
+    pub polls: u32,
+    /// Cycles Spent in Polls since Boot
+    pub cycles: u64,
+}
+
+///
+/// # Stack Usage
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct Stack {
+    /// Size in Bytes
+    pub size: u32,
+    /// High-Water Mark in Bytes
+    pub used: u32,
+}
+
+#[derive(Clone, Copy)]
+struct Snapshot {
+    time: Instant,
+    busy: u64,
+    tasks: [Option<TaskStats>; TASKS],
+}
+
+struct State {
+    /// Cycle Count at the Poll Start
+    poll: u32,
+    /// Cycle Count at the Task Poll Start
+    exec: u32,
+    /// Busy Cycles since Boot
+    busy: u64,
+    tasks: [Option<TaskStats>; TASKS],
+    /// At the Previous Report
+    last: Snapshot,
+}
+
+impl State {
+    ///
+    /// # Find or Add a Task
+    ///
+    /// `None` once the table is full, the task is then not tracked.
+    ///
+    fn task(&mut self, id: u32) -> Option<&mut TaskStats> {
+        let i = self
+            .tasks
+            .iter()
+            .position(|t: _| t.is_none_or(|t: TaskStats| t.id == id))?;
+
+        let task = self.tasks[i].get_or_insert(TaskStats {
+            id,
+            name: None,
+            polls: 0,
+            cycles: 0,
+        });
+        Some(task)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            time: Instant::now(),
+            busy: self.busy,
+            tasks: self.tasks,
+        }
+    }
+}
+
+///
+/// # Name a Task
+///
+/// Names the task of a spawn token in the report, pass the token on
+/// to the spawner.
+///
+pub fn named<S>(name: &'static str, token: SpawnToken<S>) -> SpawnToken<S> {
+    STATE.lock(|s: _| {
+        if let Some(t) = s.borrow_mut().task(token.id()) {
+            t.name = Some(name);
+        }
+    });
+    token
+}
+
+///
+/// # Get Task Run-Times
+///
+/// Tasks in spawn order, counted since boot.
+///
+pub fn tasks() -> [Option<TaskStats>; TASKS] {
+    STATE.lock(|s: _| s.borrow().tasks)
+}
+
+///
+/// # Get Busy Cycles
+///
+/// Cycles the executor spent polling since boot.
+///
+pub fn busy_cycles() -> u64 {
+    STATE.lock(|s: _| s.borrow().busy)
+}
+
+///
+/// # Get CPU Load
+///
+/// Busy share since the previous report: 0.0 ~ 1.0.
+///
+pub fn load() -> f32 {
+    let (now, last) = STATE.lock(|s: _| {
+        let s = s.borrow();
+        (s.snapshot(), s.last)
+    });
+    share(now.busy - last.busy, now.time - last.time)
+}
+
+///
+/// # Get Stack Usage
+///
+pub fn stack() -> Stack {
+    let (bottom, top) = bounds();
+    let mut p = bottom;
+    // Safety: Inside the Stack, Below the Deepest Frame Stops the Scan
+    while p < top && unsafe { read_volatile(p) } == PAINT {
+        p = p.wrapping_add(1);
+    }
+
+    Stack {
+        size: (top as usize - bottom as usize) as u32,
+        used: (top as usize - p as usize) as u32,
+    }
+}
+
+///
+/// # Request the Report
+///
+pub fn request_report() {
+    REPORT.signal(());
+}
+
+///
+/// # Report Due
+///
+/// Every [`REPORT_MS`], or on request.
+///
+pub fn report_due() -> bool {
+    let last = STATE.lock(|s: _| s.borrow().last.time);
+    let period = Duration::from_millis(REPORT_MS);
+    REPORT.try_take().is_some() || last.elapsed() >= period
+}
+
+///
+/// # Log the Report
+///
+/// CPU load and per-task run-time since the previous report, and
+/// the stack usage since boot.
+///
+pub fn log_report() {
+    let (now, last) = STATE.lock(|s: _| {
+        let mut s = s.borrow_mut();
+        let now = s.snapshot();
+        (now, core::mem::replace(&mut s.last, now))
+    });
+
+    let elapsed = now.time - last.time;
+    let stack = stack();
+    defmt::info!(
+        "CPU Load {}% over {}ms, Stack {}/{} Bytes",
+        share(now.busy - last.busy, elapsed) * 100.,
+        elapsed.as_millis(),
+        stack.used,
+        stack.size,
+    );
+
+    for (t, l) in now
+        .tasks
+        .into_iter()
+        .zip(last.tasks)
+        .filter_map(|(t, l): _| Some((t?, l)))
+    {
+        let polls = t.polls.wrapping_sub(l.map_or(0, |l: TaskStats| l.polls));
+        let cycles = t.cycles - l.map_or(0, |l: TaskStats| l.cycles);
+        defmt::info!(
+            "{=u32:#010x} {=str}: {}%, {} Polls/s, {} Cycles/Poll",
+            t.id,
+            t.name.unwrap_or("?"),
+            share(cycles, elapsed) * 100.,
+            polls as u64 * 1000 / elapsed.as_millis().max(1),
+            cycles / (polls as u64).max(1),
+        );
+    }
+}
+
+/// Share of `cycles` in the Time Elapsed
+fn share(cycles: u64, elapsed: Duration) -> f32 {
+    let total = elapsed.as_micros() * (SYSCLK_HZ / 1_000_000) as u64;
+    (cycles as f32 / total.max(1) as f32).min(1.)
+}
+
+fn bounds() -> (*mut u32, *mut u32) {
+    let bottom = (&raw const _stack_end).cast_mut();
+    let top = (&raw const _stack_start).cast_mut();
+    (bottom, top)
+}
+
+///
+/// # Paint the Stack
+///
+/// Called once by [`crate::sys_init`] before interrupts are enabled,
+/// fills the free stack below the caller with [`PAINT`].
+///
+#[inline(never)]
+pub(crate) fn paint() {
+    ll::interrupt::free(|_: _| {
+        let (bottom, _) = bounds();
+        let sp = ll::register::msp::read() as usize - MARGIN;
+
+        let mut p = bottom;
+        while (p as usize) < sp {
+            // Safety: Free Stack below the Current Frame
+            unsafe { write_volatile(p, PAINT) };
+            p = p.wrapping_add(1);
+        }
+    })
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_poll_start(_executor_id: u32) {
+    let now = DWT::cycle_count();
+    STATE.lock(|s: _| s.borrow_mut().poll = now);
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_executor_idle(_executor_id: u32) {
+    let now = DWT::cycle_count();
+    STATE.lock(|s: _| {
+        let mut s = s.borrow_mut();
+        s.busy += now.wrapping_sub(s.poll) as u64;
+    });
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_task_new(_executor_id: u32, task_id: u32) {
+    STATE.lock(|s: _| {
+        s.borrow_mut().task(task_id);
+    });
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_task_exec_begin(_executor_id: u32, _task_id: u32) {
+    let now = DWT::cycle_count();
+    STATE.lock(|s: _| s.borrow_mut().exec = now);
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_task_exec_end(_executor_id: u32, task_id: u32) {
+    let now = DWT::cycle_count();
+    STATE.lock(|s: _| {
+        let mut s = s.borrow_mut();
+        let cycles = now.wrapping_sub(s.exec) as u64;
+        if let Some(t) = s.task(task_id) {
+            t.polls = t.polls.wrapping_add(1);
+            t.cycles += cycles;
+        }
+    });
+}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_task_end(_executor_id: u32, _task_id: u32) {}
+
+#[unsafe(no_mangle)]
+fn _embassy_trace_task_ready_begin(_executor_id: u32, _task_id: u32) {}
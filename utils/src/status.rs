@@ -0,0 +1,302 @@
+//!
+//! # System Status
+//!
+//! The system mode as a state machine: every transition is checked
+//! against the allowed ones, carries a [`Reason`] and is kept in a
+//! short log. Tasks await a mode without polling.
+//!
+//! ## Get Current Mode
+//! ```rust
+//! let mode: SysMode = SysMode::get();
+//! ```
+//!
+//! ## Request a Transition
+//! ```rust
+//! SysMode::Armed.enter(Reason::Command)?;
+//! ```
+//!
+//! ## Wait for a Mode
+//! ```rust
+//! SysMode::Disarmed.wait_for().await;
+//! ```
+//!
+//! ## Recovery
+//!
+//! `Error` and `Degraded` entered because of lost devices fall back to
+//! `Disarmed` once the devices come back, never straight to `Armed`.
+//! Any other `Error` stays until reset, and `Fault` is final.
+//!
+
+use crate::atomic::{AtomicI8, Ordering::Relaxed as Order};
+use crate::prelude::sync::{blocking_mutex, waitqueue::MultiWakerRegistration};
+use crate::prelude::time::Instant;
+use bitfield_struct::bitenum;
+use blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use core::{cell::RefCell, future::poll_fn, task::Poll};
+
+/// Transitions Kept in the Log
+pub const LOG_LEN: usize = 16;
+/// Tasks Waiting on the Mode at Once
+const WAITERS: usize = 8;
+
+static MODE: AtomicI8 = AtomicI8::new(SysMode::Boot.into_bits());
+static STATE: Mutex<RM, RefCell<State>> = Mutex::new(RefCell::new(State {
+    cause: None,
+    log: [None; LOG_LEN],
+    next: 0,
+    wakers: MultiWakerRegistration::new(),
+}));
+
+///
+/// # System Mode Enumeration
+///
+#[repr(i8)]
+#[bitenum]
+#[derive(Clone, Copy, PartialEq, Eq, defmt::Format, Debug)]
+pub enum SysMode {
+    /// Unrecoverable until Reset
+    #[fallback]
+    Fault = -2,
+    /// Stopped, Recoverable by Rule
+    Error = -1,
+    Boot = 0,
+    SelfTest = 1,
+    Calibrating = 2,
+    /// Ready, Outputs Off
+    Disarmed = 3,
+    /// Running, Outputs On
+    Armed = 4,
+    /// A Non-Critical Device is Lost
+    Degraded = 5,
+}
+
+///
+/// # Transition Reason
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug, defmt::Format)]
+pub enum Reason {
+    /// Boot Sequence Finished
+    Booted,
+    /// Power-On Self-Test Started
+    SelfTest,
+    /// Power-On Self-Test Failed
+    SelfTestFailed,
+    /// Calibration Started
+    Calibration,
+    /// Calibration Finished
+    Calibrated,
+    /// Calibration Failed
+    CalibrationFailed,
+    /// Operator Command
+    Command,
+    /// A Device was Lost
+    DeviceLost,
+    /// Lost Devices Came Back
+    DeviceRecovered,
+    /// Unrecoverable Hardware Condition
+    Hardware,
+    /// Repeated Crashes after Boot
+    BootLoop,
+}
+
+impl Reason {
+    ///
+    /// # Caused by Devices
+    ///
+    /// Modes entered for these reasons may recover.
+    ///
+    pub const fn is_device(self) -> bool {
+        matches!(self, Self::DeviceLost | Self::DeviceRecovered)
+    }
+}
+
+///
+/// # Transition Log Entry
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct Entry {
+    pub from: SysMode,
+    pub to: SysMode,
+    pub reason: Reason,
+    pub time: Instant,
+}
+
+///
+/// # Rejected Transition
+///
+#[derive(Clone, Copy, PartialEq, Debug, defmt::Format)]
+pub struct Rejected {
+    pub from: SysMode,
+    pub to: SysMode,
+    pub reason: Reason,
+}
+
+struct State {
+    /// Reason the Current Mode was Entered
+    cause: Option<Reason>,
+    log: [Option<Entry>; LOG_LEN],
+    next: usize,
+    wakers: MultiWakerRegistration<WAITERS>,
+}
+
+///
+/// # Transition Guard
+///
+const fn allowed(from: SysMode, to: SysMode, cause: Option<Reason>) -> bool {
+    use SysMode::*;
+
+    let recoverable = match cause {
+        Some(x) => x.is_device(),
+        None => false,
+    };
+
+    match (from, to) {
+        (Fault, _) => false,
+        (_, Fault | Error) => true,
+        (Boot, SelfTest | Calibrating | Disarmed) => true,
+        (SelfTest, Calibrating | Disarmed) => true,
+        (Calibrating, Disarmed) => true,
+        // Never Calibrate while Armed or Degraded
+        (Disarmed, Calibrating) => true,
+        (Disarmed, Armed) | (Armed, Disarmed) => true,
+        (Disarmed | Armed, Degraded) => true,
+        (Error, Degraded) | (Error | Degraded, Disarmed) => recoverable,
+        _ => false,
+    }
+}
+
+impl SysMode {
+    ///
+    /// # Get System Mode
+    ///
+    /// Retrieve the current system mode.
+    ///
+    #[inline]
+    pub fn get() -> SysMode {
+        SysMode::from_bits(MODE.load(Order))
+    }
+
+    ///
+    /// # Get Cause
+    ///
+    /// The reason the current mode was entered, `None` while booting.
+    ///
+    pub fn cause() -> Option<Reason> {
+        STATE.lock(|s: _| s.borrow().cause)
+    }
+
+    ///
+    /// # Transition Allowed
+    ///
+    /// Whether [`Self::enter`] would accept this mode right now.
+    ///
+    pub fn can_enter(self) -> bool {
+        STATE.lock(|s: _| {
+            let from = Self::get();
+            from == self || allowed(from, self, s.borrow().cause)
+        })
+    }
+
+    ///
+    /// # Enter System Mode
+    ///
+    /// Transition to this mode if allowed from the current one,
+    /// entering the current mode again does nothing.
+    ///
+    pub fn enter(self, reason: Reason) -> Result<(), Rejected> {
+        let res = STATE.lock(|s: _| {
+            let mut s = s.borrow_mut();
+            let from = Self::get();
+
+            if from == self {
+                return Ok(None);
+            }
+
+            if !allowed(from, self, s.cause) {
+                let to = self;
+                return Err(Rejected { from, to, reason });
+            }
+
+            let entry = Entry {
+                from,
+                to: self,
+                reason,
+                time: Instant::now(),
+            };
+
+            MODE.store(self.into_bits(), Order);
+            s.cause = Some(reason);
+            let i = s.next;
+            (s.log[i], s.next) = (Some(entry), (i + 1) % LOG_LEN);
+            s.wakers.wake();
+
+            Ok(Some(entry))
+        });
+
+        match res {
+            Ok(Some(e)) => {
+                defmt::info!("System Mode: {:?} -> {:?} ({:?})", e.from, e.to, e.reason);
+                Ok(())
+            }
+            Ok(None) => Ok(()),
+            Err(e) => {
+                defmt::warn!("System Mode Transition Rejected: {:?}", e);
+                Err(e)
+            }
+        }
+    }
+
+    ///
+    /// # Wait for System Mode
+    ///
+    /// Wait until the system mode matches the specified mode.
+    ///
+    pub async fn wait_for(self) {
+        Self::wait_until(|m: _| m == self).await;
+    }
+
+    ///
+    /// # Wait until a Condition
+    ///
+    /// Returns the first mode satisfying the condition.
+    ///
+    pub async fn wait_until(f: impl Fn(SysMode) -> bool) -> SysMode {
+        poll_fn(|cx: _| {
+            STATE.lock(|s: _| {
+                let mode = Self::get();
+                match f(mode) {
+                    true => Poll::Ready(mode),
+                    false => {
+                        s.borrow_mut().wakers.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+
+    ///
+    /// # Get Transition Log
+    ///
+    /// Returns the latest transitions, oldest first.
+    ///
+    pub fn history() -> [Option<Entry>; LOG_LEN] {
+        STATE.lock(|s: _| {
+            let s = s.borrow();
+            let mut log = s.log;
+            log.rotate_left(s.next);
+            log
+        })
+    }
+
+    ///
+    /// # Log Transition History
+    ///
+    pub fn log_history() {
+        defmt::info!("System Mode: {:?}, History:", Self::get());
+        for e in Self::history().into_iter().flatten() {
+            defmt::info!("{}: {:?} -> {:?} ({:?})", e.time, e.from, e.to, e.reason);
+        }
+    }
+}
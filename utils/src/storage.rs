@@ -0,0 +1,159 @@
+//!
+//! # Non-Volatile Storage
+//!
+//! Small records kept in the last sector of the internal flash, which
+//! is reserved in `memory.x`. The sector holds [`SLOTS`] fixed slots of
+//! [`SLOT_SIZE`] bytes, each with a header and a CRC-32, so a missing
+//! or corrupted record simply reads as absent.
+//!
+//! The whole region is mirrored in RAM. Storing rewrites the sector,
+//! the CPU stalls on flash access for the erase time (about
+//! [`ERASE_MS`]), so only store from a state where that is acceptable,
+//! e.g. calibration. The rewrite is timed by the `Flash Rewrite` probe,
+//! see [`crate::probe`].
+//!
+//! ## Init Once, before any Access
+//! ```rust
+//! utils::storage::init(p.FLASH);
+//! ```
+//!
+//! ## Load and Store
+//! ```rust
+//! let mut buf = [0; 64];
+//! let len: Option<usize> = storage::load(SLOT, &mut buf).await;
+//! storage::store(SLOT, &buf[..len]).await?;
+//! ```
+//!
+
+use crate::crc::crc32;
+use crate::prelude::{hal, sync};
+use hal::flash::{Blocking, Error as FlashError, Flash, WRITE_SIZE};
+use hal::{Peri, peripherals::FLASH};
+use sync::{blocking_mutex::raw::CriticalSectionRawMutex as RM, mutex::Mutex};
+
+/// Reserved Sector Offset from the Flash Base: Sector 7
+const SECTOR: u32 = 0xE_0000;
+/// Reserved Sector Size
+const SECTOR_SIZE: u32 = 128 * 1024;
+
+/// Sector Erase Stall in ms, Typical
+pub const ERASE_MS: u32 = 2000;
+
+/// Number of Slots
+pub const SLOTS: usize = 8;
+/// Slot Size in Bytes, Header Included
+pub const SLOT_SIZE: usize = 256;
+/// Slot Header: Magic(4) + Length(2) + Reserved(2) + CRC(4)
+const HEADER: usize = 12;
+/// Maximum Record Length in Bytes
+pub const RECORD_MAX: usize = SLOT_SIZE - HEADER;
+
+/// Slot Header Magic
+const MAGIC: u32 = 0x544F_4C53; // "SLOT"
+
+/// Mirrored Region Size
+const REGION: usize = SLOTS * SLOT_SIZE;
+
+const _: () = assert!(SLOT_SIZE.is_multiple_of(WRITE_SIZE));
+const _: () = assert!(REGION <= SECTOR_SIZE as usize);
+
+static STORAGE: Mutex<RM, Option<Storage>> = Mutex::new(None);
+
+///
+/// # Storage Error
+///
+#[derive(Debug, Clone, Copy, PartialEq, defmt::Format)]
+pub enum StorageError {
+    /// [`init`] not Called
+    Uninit,
+    /// Slot out of Range, or Record Longer than [`RECORD_MAX`]
+    Size,
+    /// Flash Erase or Program Failed
+    Flash(FlashError),
+}
+
+struct Storage {
+    flash: Flash<'static, Blocking>,
+    image: [u8; REGION],
+}
+
+///
+/// # Init Storage
+///
+/// Takes the flash peripheral and mirrors the reserved region.
+///
+pub fn init(p: Peri<'static, FLASH>) {
+    let mut flash = Flash::new_blocking(p);
+    let mut image = [0xFF; REGION];
+
+    if let Err(e) = flash.blocking_read(SECTOR, &mut image) {
+        defmt::error!("{}: Storage Read Failed: {:?}", file!(), e);
+    }
+
+    match STORAGE.try_lock() {
+        Ok(mut s) if s.is_none() => *s = Some(Storage { flash, image }),
+        _ => panic!("{}: Can Be Called Only Once!!!", file!()),
+    }
+}
+
+///
+/// # Load Record
+///
+/// Copies the record of `slot` into `buf`, returns its length.
+/// Returns `None` if the slot is empty, corrupted or `buf` is too short.
+///
+pub async fn load(slot: usize, buf: &mut [u8]) -> Option<usize> {
+    let s = STORAGE.lock().await;
+    let image = &s.as_ref()?.image;
+    let raw = image.get(slot * SLOT_SIZE..)?.get(..SLOT_SIZE)?;
+
+    let (head, body) = raw.split_at(HEADER);
+    let magic = u32::from_le_bytes([head[0], head[1], head[2], head[3]]);
+    let len = u16::from_le_bytes([head[4], head[5]]) as usize;
+    let crc = u32::from_le_bytes([head[8], head[9], head[10], head[11]]);
+
+    if magic != MAGIC || len > RECORD_MAX || crc32(&body[..len]) != crc {
+        return None;
+    }
+
+    buf.get_mut(..len)?.copy_from_slice(&body[..len]);
+    Some(len)
+}
+
+///
+/// # Store Record
+///
+/// Replaces the record of `slot`, all other slots are kept.
+/// Blocks the CPU while the sector is erased and programmed.
+///
+pub async fn store(slot: usize, data: &[u8]) -> Result<(), StorageError> {
+    if slot >= SLOTS || data.len() > RECORD_MAX {
+        return Err(StorageError::Size);
+    }
+
+    let mut s = STORAGE.lock().await;
+    let Storage { flash, image } = s.as_mut().ok_or(StorageError::Uninit)?;
+
+    let raw = &mut image[slot * SLOT_SIZE..][..SLOT_SIZE];
+    raw.fill(0xFF);
+    raw[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+    raw[4..6].copy_from_slice(&(data.len() as u16).to_le_bytes());
+    raw[6..8].copy_from_slice(&[0; 2]);
+    raw[8..12].copy_from_slice(&crc32(data).to_le_bytes());
+    raw[HEADER..][..data.len()].copy_from_slice(data);
+
+    let res = crate::measure!(
+        "Flash Rewrite",
+        flash
+            .blocking_erase(SECTOR, SECTOR + SECTOR_SIZE)
+            .and_then(|_| flash.blocking_write(SECTOR, image))
+    );
+
+    // Keep the Mirror in Sync with what the Flash really Holds
+    if let Err(e) = res {
+        let _ = flash.blocking_read(SECTOR, image);
+        return Err(StorageError::Flash(e));
+    }
+
+    Ok(())
+}
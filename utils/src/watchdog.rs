@@ -0,0 +1,397 @@
+//!
+//! # Task Watchdog
+//!
+//! Runs IWDG1 and pets it only while every task declared with
+//! [`watchdog!`] has checked in within its deadline, so a deadlocked
+//! executor or a stuck task resets the chip.
+//!
+//! While starving, the late task is recorded in backup SRAM, which
+//! survives the reset and is reported on the next boot.
+//!
+//! ## Example
+//! ```rust
+//! utils::watchdog! {
+//!     timeout_ms: 5000;
+//!
+//!     Controller => { deadline_ms: 100 },
+//! }
+//!
+//! // Health Task
+//! let mut wdg = Task::watchdog(p.iwdg_p);
+//! loop {
+//!     Task::feed(&mut wdg);
+//! }
+//!
+//! // Controller Task
+//! Task::Controller.check_in();
+//! ```
+//!
+//! [`watchdog!`]: crate::watchdog
+//!
+
+use crate::atomic::{AtomicBool, AtomicU64, Ordering::Relaxed as Order};
+use crate::crash::{self, ResetReason};
+use crate::crc::crc32;
+use crate::prelude::hal::{Peri, peripherals::IWDG1, wdg::IndependentWatchdog};
+use crate::prelude::sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex as RM};
+use crate::prelude::time::{Duration, Instant};
+use core::cell::Cell;
+use core::ptr::{read_volatile, write_volatile};
+
+/// Task Name Bytes Kept in the Record
+const NAME_MAX: usize = 16;
+const MAGIC: u32 = u32::from_le_bytes(*b"WDOG");
+
+/// Missed Check-In: Magic, Late ms, Uptime ms, Name, CRC
+#[unsafe(link_section = ".bsram.watchdog")]
+static mut RECORD: [u32; 8] = [0; _];
+
+static PREVIOUS: Mutex<RM, Cell<Option<Missed>>> = Mutex::new(Cell::new(None));
+
+///
+/// # Get Previous Missed Check-In
+///
+/// The late task recorded before the last reset, `None` if the
+/// watchdog was never starved or has not been started yet.
+///
+pub fn previous() -> Option<Missed> {
+    PREVIOUS.lock(|p: _| p.get())
+}
+
+///
+/// # Missed Check-In
+///
+#[derive(Clone, Copy, PartialEq)]
+pub struct Missed {
+    name: [u8; NAME_MAX],
+    /// Time past the Deadline in ms
+    pub late_ms: u32,
+    /// Uptime in ms
+    pub uptime_ms: u32,
+}
+
+impl Missed {
+    ///
+    /// # Task Name
+    ///
+    pub fn name(&self) -> &str {
+        let len = self.name.iter().position(|&b: _| b == 0);
+        let name = &self.name[..len.unwrap_or(NAME_MAX)];
+        core::str::from_utf8(name).unwrap_or("?")
+    }
+
+    fn to_words(self) -> [u32; 8] {
+        let mut w = [MAGIC, self.late_ms, self.uptime_ms, 0, 0, 0, 0, 0];
+        for (w, b) in w[3..7].iter_mut().zip(self.name.as_chunks::<4>().0) {
+            *w = u32::from_le_bytes(*b);
+        }
+        w[7] = checksum(&w);
+        w
+    }
+
+    fn from_words(w: [u32; 8]) -> Option<Self> {
+        if w[0] != MAGIC || w[7] != checksum(&w) {
+            return None;
+        }
+
+        let mut name = [0; NAME_MAX];
+        for (b, w) in name.as_chunks_mut::<4>().0.iter_mut().zip(&w[3..7]) {
+            *b = w.to_le_bytes();
+        }
+
+        Some(Self {
+            name,
+            late_ms: w[1],
+            uptime_ms: w[2],
+        })
+    }
+}
+
+impl defmt::Format for Missed {
+    fn format(&self, fmt: defmt::Formatter) {
+        defmt::write!(
+            fmt,
+            "{=str} Late by {}ms at {}ms Uptime",
+            self.name(),
+            self.late_ms,
+            self.uptime_ms
+        );
+    }
+}
+
+fn checksum(w: &[u32; 8]) -> u32 {
+    let mut bytes = [0; 28];
+    for (b, w) in bytes.as_chunks_mut::<4>().0.iter_mut().zip(&w[..7]) {
+        *b = w.to_le_bytes();
+    }
+    crc32(&bytes)
+}
+
+fn load() -> Option<Missed> {
+    // Safety: Only Accessed by the Watchdog Owner, Any Content is Valid
+    let w = unsafe { read_volatile(&raw const RECORD) };
+    Missed::from_words(w)
+}
+
+fn store(missed: Option<Missed>) {
+    let w = missed.map(Missed::to_words).unwrap_or([0; _]);
+    // Safety: Only Accessed by the Watchdog Owner
+    unsafe { write_volatile(&raw mut RECORD, w) };
+}
+
+///
+/// # Task Check-In
+///
+pub struct CheckIn {
+    /// Last Check-In in Ticks
+    last: AtomicU64,
+    parked: AtomicBool,
+}
+
+impl Default for CheckIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CheckIn {
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+            parked: AtomicBool::new(false),
+        }
+    }
+
+    ///
+    /// # Check In
+    ///
+    /// Also resumes watching a parked task.
+    ///
+    pub fn check_in(&self) {
+        self.last.store(Instant::now().as_ticks(), Order);
+        self.parked.store(false, Order);
+    }
+
+    ///
+    /// # Park
+    ///
+    /// Stop watching a task that has stopped on purpose.
+    ///
+    pub fn park(&self) {
+        self.parked.store(true, Order);
+    }
+
+    ///
+    /// # Time past the Deadline
+    ///
+    /// `None` if in time or parked. Counts from boot before the
+    /// first check-in.
+    ///
+    pub fn late(&self, deadline: Duration) -> Option<Duration> {
+        if self.parked.load(Order) {
+            return None;
+        }
+
+        let last = Instant::from_ticks(self.last.load(Order));
+        let elapsed = Instant::now().saturating_duration_since(last);
+        elapsed
+            .checked_sub(deadline)
+            .filter(|d: _| d.as_ticks() > 0)
+    }
+}
+
+///
+/// # Independent Watchdog
+///
+pub struct Watchdog {
+    iwdg: IndependentWatchdog<'static, IWDG1>,
+    starving: bool,
+}
+
+impl Watchdog {
+    ///
+    /// # Start Watchdog
+    ///
+    /// Reports a missed check-in that reset the chip, then starts
+    /// IWDG1, which cannot be stopped until the next reset.
+    ///
+    pub fn new(p: Peri<'static, IWDG1>, timeout_ms: u32) -> Self {
+        if let Some(missed) = load() {
+            match crash::reset_reason() == ResetReason::Watchdog {
+                true => defmt::error!("Watchdog Reset: {:?}", missed),
+                false => defmt::warn!("Watchdog Starved before Reset: {:?}", missed),
+            }
+            PREVIOUS.lock(|p: _| p.set(Some(missed)));
+            store(None);
+        }
+
+        let mut iwdg = IndependentWatchdog::new(p, timeout_ms * 1000);
+        iwdg.unleash();
+        defmt::info!("Watchdog Started: {}ms", timeout_ms);
+
+        Self {
+            iwdg,
+            starving: false,
+        }
+    }
+
+    ///
+    /// # Feed Watchdog
+    ///
+    /// Pets the watchdog if no task is late, otherwise records the
+    /// late task and lets the watchdog starve.
+    ///
+    pub fn feed(&mut self, late: Option<(&'static str, Duration)>) {
+        let Some((name, late)) = late else {
+            if self.starving {
+                defmt::info!("Watchdog: All Tasks Checked In");
+                self.starving = false;
+                store(None);
+            }
+            return self.iwdg.pet();
+        };
+
+        if !self.starving {
+            defmt::error!("Watchdog Starving: {=str} Missed its Check-In!!!", name);
+            self.starving = true;
+        }
+
+        let mut bytes = [0; NAME_MAX];
+        let len = name.len().min(NAME_MAX);
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+        store(Some(Missed {
+            name: bytes,
+            late_ms: late.as_millis() as u32,
+            uptime_ms: Instant::now().as_millis() as u32,
+        }));
+    }
+}
+
+///
+/// # watchdog
+///
+/// Declare the tasks watched by the watchdog, each with the longest
+/// time allowed between its check-ins.
+///
+/// Generates `Task` with one variant per entry and its check-in table.
+///
+#[macro_export]
+macro_rules! watchdog {
+    (
+        timeout_ms: $timeout:expr;
+
+        $(
+            $(#[$meta:meta])*
+            $task:ident => { deadline_ms: $deadline:expr $(,)? }
+        ),* $(,)?
+    ) => {
+        ///
+        /// # Watched Task Enumeration
+        ///
+        #[derive(Clone, Copy, PartialEq, Eq, Debug, ::defmt::Format)]
+        pub enum Task {
+            $( $(#[$meta])* $task, )*
+        }
+
+        static CHECK_INS: [$crate::watchdog::CheckIn; Task::COUNT] =
+            [const { $crate::watchdog::CheckIn::new() }; Task::COUNT];
+
+        const _: () = {
+            let mut i = 0;
+            while i < Task::COUNT {
+                assert!(Task::ALL[i].deadline_ms() < Task::TIMEOUT_MS);
+                i += 1;
+            }
+        };
+
+        /// Settings for the Watchdog
+        impl Task {
+            /// Watchdog Timeout in ms
+            pub const TIMEOUT_MS: u32 = $timeout;
+
+            /// Number of Tasks
+            pub const COUNT: usize = <[Task]>::len(&[$(Task::$task),*]);
+
+            /// All Tasks in Declaration Order
+            pub const ALL: [Task; Task::COUNT] = [$(Task::$task),*];
+
+            ///
+            /// # Task Name
+            ///
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $( Self::$task => stringify!($task), )*
+                }
+            }
+
+            ///
+            /// # Check-In Deadline in ms
+            ///
+            pub const fn deadline_ms(self) -> u32 {
+                match self {
+                    $( Self::$task => $deadline, )*
+                }
+            }
+
+            ///
+            /// # Check In
+            ///
+            pub fn check_in(self) {
+                CHECK_INS[self as usize].check_in()
+            }
+
+            ///
+            /// # Park
+            ///
+            /// Stop watching this task until it checks in again.
+            ///
+            pub fn park(self) {
+                CHECK_INS[self as usize].park()
+            }
+
+            ///
+            /// # Park All
+            ///
+            /// Stop watching every task, each resumes on its next check-in.
+            ///
+            pub fn park_all() {
+                Self::ALL.into_iter().for_each(Self::park)
+            }
+
+            ///
+            /// # Latest Task
+            ///
+            /// The task furthest past its deadline, if any.
+            ///
+            pub fn late() -> Option<(Task, $crate::prelude::time::Duration)> {
+                Self::ALL
+                    .into_iter()
+                    .filter_map(|t: Task| {
+                        let deadline =
+                            $crate::prelude::time::Duration::from_millis(t.deadline_ms() as u64);
+                        CHECK_INS[t as usize].late(deadline).map(|d: _| (t, d))
+                    })
+                    .max_by_key(|&(_, d): &(Task, _)| d)
+            }
+
+            ///
+            /// # Start Watchdog
+            ///
+            pub fn watchdog(
+                p: $crate::prelude::hal::Peri<'static, $crate::prelude::hal::peripherals::IWDG1>,
+            ) -> $crate::watchdog::Watchdog {
+                $crate::watchdog::Watchdog::new(p, Self::TIMEOUT_MS)
+            }
+
+            ///
+            /// # Feed Watchdog
+            ///
+            /// Pets the watchdog only if every task is in time.
+            ///
+            pub fn feed(wdg: &mut $crate::watchdog::Watchdog) {
+                wdg.feed(Self::late().map(|(t, d): (Task, _)| (t.name(), d)))
+            }
+        }
+    };
+}